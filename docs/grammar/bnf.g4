@@ -23,10 +23,31 @@ expr
 	| fnCall
 	| '(' expr ')'
 	| list
+	| typeExpr
+	;
+
+typeExpr
+	: 'type' typeSize? predicate?
+	;
+
+typeSize
+	: IntLiteral ('..' '='? IntLiteral)? ('default' IntLiteral)?
+	;
+
+predicate
+	: param+ list	// A single named parameter
 	;
 
 lambda
-	: 'fn' param* list
+	: 'fn' param* whereClause? list
+	;
+
+whereClause
+	: 'where' (constraint ',')* constraint?
+	;
+
+constraint
+	: Id ':' Id ('+' Id)*
 	;
 
 fnCall
@@ -41,7 +62,8 @@ argument
 
 param
 	: anyToken
-	| '[' Id? (':' Id)? ']'
+	| '[' Id? (':' Id ('+' Id)*)? ']'
+	| '(' param* ')'	// Group
 	;
 
 anyToken
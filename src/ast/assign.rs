@@ -6,6 +6,8 @@ use super::*;
 pub struct Assign<'source> {
     pub name:  &'source str,
     pub value: Rc<Expr<'source>>,
+    pub docs:  Vec<&'source str>,
+    pub span:  Span,
 }
 
 impl PartialEq for Assign<'_> {
@@ -18,12 +20,19 @@ impl<'source> AstNode<'source> for Assign<'source> {
     fn build_context(&self, ctx: &mut ProgramContext<'source>, scope_stack: &mut Vec<ScopeId>) {
         if let Expr::Type(type_) = self.value.as_ref() {
             ctx.types.insert(self.name, type_.clone());
+            ctx.type_docs.insert(self.name, self.docs.clone());
         } else {
             // Other expressions into symbols
+            if let Expr::FnDef(fn_def) = self.value.as_ref() {
+                check_overloads(ctx, self.name, fn_def, *scope_stack.last().unwrap());
+            }
             ctx.symbols.entry(self.name).or_default().push(Symbol {
                 scope: *scope_stack.last().unwrap(),
                 value: Rc::clone(&self.value),
+                docs:  self.docs.clone(),
             });
+            // Names in function bodies and blocks
+            self.value.build_context(ctx, scope_stack);
         }
     }
 
@@ -49,30 +58,112 @@ impl<'source> AstNode<'source> for Assign<'source> {
                 type_name = self.name,
                 size = type_.size
             )?,
-            Expr::SmallValue(value) => {
+            Expr::SmallValue(value, _) => self.emit_move(output, ctx, value)?,
+            Expr::Register(register, _) => self.emit_move(output, ctx, register)?,
+            // The name stands for the destination of the move
+            Expr::Move(move_) => move_.check_and_emit(output, ctx, scope_stack)?,
+            Expr::List(list) if list.is_data() => list.emit_data(output, ctx, self.name)?,
+            Expr::List(list) => {
+                if let Some(value) = list.elements.elements.iter().find(|expr| expr.is_value()) {
+                    return Err(Diagnostic::error(
+                        list.span.clone(),
+                        format!("`{}` mixes values and instructions", self.name),
+                    )
+                    .with_code("mixed-list")
+                    .with_label(value.span(), "a value among instructions")
+                    .with_help("A list is either all values or a block of instructions")
+                    .into());
+                }
+                writeln!(output, "\n|| Block {name}\n{name}: ", name = self.name)?;
+                change_indentation(IndentationChange::More);
+                list.check_and_emit(output, ctx, scope_stack)?;
+                change_indentation(IndentationChange::Less);
+            }
+            Expr::Local(..) => unreachable!("Locals are bound by functions, not parsed"),
+            Expr::Assign(assign) => {
+                return Err(Diagnostic::error(
+                    assign.span.clone(),
+                    "Assignments can't be chained".to_string(),
+                )
+                .with_code("chained-assign")
+                .with_primary_label("an assignment has no value")
+                .into())
+            }
+            Expr::Bytes(bytes, _) => {
                 writeln!(
                     output,
-                    "move {}b {} -> {}",
-                    value.number_bytes(ctx),
-                    value,
-                    self.name
+                    "\n|| Const string {name}\n{name}: ",
+                    name = self.name
+                )?;
+                writeln!(
+                    output,
+                    "{}    data {}b {:?}",
+                    current_padding(),
+                    bytes.len(),
+                    self.value
                 )?;
             }
-            Expr::Assign(_) => todo!(),
-            Expr::Bytes(_) => todo!(),
-            Expr::StringSlice(_) => todo!(),
+            Expr::FormatString(format_string) => {
+                return compile_error(
+                    format_string.span.clone(),
+                    "unsupported-format-string",
+                    "Format strings can only be used as arguments".to_string(),
+                )
+            }
         }
         Ok(())
     }
 }
 
-/// Should be called when on the next token after '='
+impl Assign<'_> {
+    /// Move `value` to the name. Uses all of the space the value allows, like
+    /// a move does
+    fn emit_move<'a, Output: io::Write>(
+        &self,
+        output: &mut Output,
+        ctx: &'a ProgramContext,
+        value: &(impl CompTimeSize<'a> + std::fmt::Display),
+    ) -> CheckResult<()> {
+        let Some(size) = value.number_bytes(ctx).largest() else {
+            return compile_error(
+                self.span.clone(),
+                "unknown-size",
+                format!("Unknown size of {}", self.name),
+            );
+        };
+        writeln!(output, "move {size}b {value} -> {}", self.name)?;
+        Ok(())
+    }
+}
+
+/// Should be called when on the next token after '='. `start` is where the
+/// name begins
 pub fn parse_assign<'source>(
     parser: &mut Parser<'source>,
     name: &'source str,
+    start: usize,
 ) -> Result<Expr<'source>, ParsingError<'source>> {
+    let value = Expr::parse(parser)?;
     Ok(Expr::Assign(Assign {
         name,
-        value: Expr::parse(parser)?.into(),
+        span: start..value.span().end,
+        value: value.into(),
+        docs: vec![],
     }))
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::compile_to_string;
+
+    #[test]
+    fn assign_concrete_sizes() {
+        let output = compile_to_string("x = r1,\ny = 5,\nz = 2u16,\n").unwrap();
+        let lines = output.lines().map(str::trim).collect::<Vec<_>>();
+        assert_eq!(lines, [
+            "move 8b r1 -> x",
+            "move 8b 0x0000000000000005 -> y",
+            "move 2b 0x0002 -> z",
+        ]);
+    }
+}
@@ -1,6 +1,6 @@
 use super::*;
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct Ast<'source> {
     pub program: ListContent<'source>,
 }
@@ -8,7 +8,7 @@ pub struct Ast<'source> {
 impl<'source> Ast<'source> {
     pub fn build_context(&self, ctx: &mut ProgramContext<'source>) {
         reset_scope_and_indent();
-        self.program.build_context(ctx, &mut vec![next_scope()]);
+        self.program.build_context(ctx, &mut vec![]);
     }
 
     pub fn expand_clayer<Output: std::io::Write>(
@@ -17,8 +17,7 @@ impl<'source> Ast<'source> {
         ctx: &ProgramContext,
     ) -> CheckResult<()> {
         reset_scope_and_indent();
-        self.program
-            .check_and_emit(output, ctx, &mut vec![next_scope()])
+        self.program.check_and_emit(output, ctx, &mut vec![])
     }
 }
 
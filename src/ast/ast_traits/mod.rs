@@ -4,4 +4,4 @@ pub use ast_node::AstNode;
 mod comptime_size;
 pub use comptime_size::CompTimeSize;
 
-pub(self) use super::*;
+use super::*;
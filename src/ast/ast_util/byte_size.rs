@@ -1,9 +1,10 @@
-use std::{fmt, ops};
+use std::{cmp::Ordering, fmt, ops};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum ByteSize {
     Exact(usize),
-    Range(ops::Range<usize>),
+    /// Any size from the start to the end, both included
+    Range(ops::RangeInclusive<usize>),
     AnySize,
 }
 
@@ -11,15 +12,31 @@ impl fmt::Display for ByteSize {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             ByteSize::Exact(val) => write!(f, "{}b", val),
-            ByteSize::Range(ops::Range { start, end }) => {
-                write!(f, "({start}..{end})b")
-            }
+            ByteSize::Range(range) => write!(f, "({}..={})b", range.start(), range.end()),
             ByteSize::AnySize => write!(f, "AnySize"),
         }
     }
 }
 
 impl ByteSize {
+    /// The most bytes a value of this size can have, `None` if it is unknown
+    pub fn largest(&self) -> Option<usize> {
+        match self {
+            ByteSize::Exact(size) => Some(*size),
+            ByteSize::Range(range) => Some(*range.end()),
+            ByteSize::AnySize => None,
+        }
+    }
+
+    /// The fewest bytes a value of this size can have, `None` if it is unknown
+    pub fn smallest(&self) -> Option<usize> {
+        match self {
+            ByteSize::Exact(size) => Some(*size),
+            ByteSize::Range(range) => Some(*range.start()),
+            ByteSize::AnySize => None,
+        }
+    }
+
     pub fn overlap(&self, other: &ByteSize) -> Option<ByteSize> {
         match self {
             ByteSize::AnySize => Some(other.clone()),
@@ -27,29 +44,60 @@ impl ByteSize {
                 // 4 <-> 4 => 4
                 ByteSize::Exact(other_size) if other_size == size => Some(ByteSize::Exact(*size)),
 
-                // 4 <-> 1..8 => 4
-                ByteSize::Range(other_range) if other_range.contains(&size) => {
+                // 4 <-> 1..=8 => 4
+                ByteSize::Range(other_range) if other_range.contains(size) => {
                     Some(ByteSize::Exact(*size))
                 }
 
-                ByteSize::AnySize => Some(ByteSize::AnySize),
-                // 4 <-> 1..2 => None
+                // 4 <-> any => 4
+                ByteSize::AnySize => Some(ByteSize::Exact(*size)),
+                // 4 <-> 1..=2 => None
                 _ => None,
             },
-            ByteSize::Range(ref self_range) => match other {
-                // 1..8 <-> 4 => 4
-                ByteSize::Exact(other_size) if self_range.contains(&other_size) => {
+            ByteSize::Range(self_range) => match other {
+                // 1..=8 <-> 4 => 4
+                ByteSize::Exact(other_size) if self_range.contains(other_size) => {
                     Some(ByteSize::Exact(*other_size))
                 }
-                // 1..4 <-> 2..8 => 2..4
-                ByteSize::Range(other_range) => Some(ByteSize::Range(
-                    self_range.start.max(other_range.start)..self_range.end.min(other_range.end),
-                )),
+                // 1..=4 <-> 2..=8 => 2..=4
+                ByteSize::Range(other_range) => {
+                    let start = *self_range.start().max(other_range.start());
+                    let end = *self_range.end().min(other_range.end());
+                    match start.cmp(&end) {
+                        // 1..=4 <-> 4..=8 => 4
+                        Ordering::Equal => Some(ByteSize::Exact(start)),
+                        Ordering::Less => Some(ByteSize::Range(start..=end)),
+                        // 1..=2 <-> 4..=8
+                        Ordering::Greater => None,
+                    }
+                }
+                ByteSize::AnySize => Some(ByteSize::Range(self_range.clone())),
 
-                // 1..2 <-> 4
-                // 1..2 <-> 4..8
+                // 1..=2 <-> 4
                 _ => None,
             },
         }
     }
 }
+
+/// The size of two values next to each other
+impl ops::Add for ByteSize {
+    type Output = ByteSize;
+
+    fn add(self, other: ByteSize) -> ByteSize {
+        match (self, other) {
+            (ByteSize::AnySize, _) | (_, ByteSize::AnySize) => ByteSize::AnySize,
+            // 1 + 2 => 3
+            (ByteSize::Exact(a), ByteSize::Exact(b)) => ByteSize::Exact(a + b),
+            // 1 + 1..=8 => 2..=9
+            (ByteSize::Exact(size), ByteSize::Range(range))
+            | (ByteSize::Range(range), ByteSize::Exact(size)) => {
+                ByteSize::Range(range.start() + size..=range.end() + size)
+            }
+            // 1..=8 + 1..=8 => 2..=16
+            (ByteSize::Range(a), ByteSize::Range(b)) => {
+                ByteSize::Range(a.start() + b.start()..=a.end() + b.end())
+            }
+        }
+    }
+}
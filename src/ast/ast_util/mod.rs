@@ -1,7 +1,10 @@
 mod byte_size;
 pub use byte_size::ByteSize;
 
+mod edit_distance;
+pub use edit_distance::edit_distance;
+
 pub mod register;
 pub mod scope;
 
-pub(self) use super::*;
+use super::*;
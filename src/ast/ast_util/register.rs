@@ -1,13 +1,18 @@
 use super::*;
 
-pub struct Register(u8);
+/// One of the registers `r0`, `r1`, ..
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Register(pub u8);
+
+impl CompTimeSize<'_> for Register {
+    fn number_bytes(&self, _: &ProgramContext) -> ByteSize {
+        // Anything up to 8 bytes fits
+        ByteSize::Range(1..=8)
+    }
+}
 
 impl std::fmt::Display for Register {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "r{}", self.0)
     }
 }
-
-pub fn free_register() -> Register {
-    return Register(0);
-}
@@ -1,13 +1,12 @@
-use std::{
-    collections::HashMap,
-    rc::Rc,
-    sync::atomic::{AtomicUsize, Ordering},
-};
+use std::{cell::Cell, collections::HashMap, rc::Rc};
 
 use super::*;
 
-static HIGHEST_SCOPE: AtomicUsize = AtomicUsize::new(0);
-static INDENTATION_LEVEL: AtomicUsize = AtomicUsize::new(0);
+// Per thread, so tests that compile in parallel don't share them
+thread_local! {
+    static HIGHEST_SCOPE: Cell<usize> = const { Cell::new(0) };
+    static INDENTATION_LEVEL: Cell<usize> = const { Cell::new(0) };
+}
 
 pub enum IndentationChange {
     More,
@@ -15,23 +14,26 @@ pub enum IndentationChange {
 }
 
 pub fn current_padding() -> String {
-    " ".repeat(INDENTATION_LEVEL.load(Ordering::Relaxed) * 4)
+    " ".repeat(INDENTATION_LEVEL.get() * 4)
 }
 
 pub fn next_scope() -> usize {
-    HIGHEST_SCOPE.fetch_add(1, Ordering::Relaxed)
+    HIGHEST_SCOPE.replace(HIGHEST_SCOPE.get() + 1)
 }
 
 pub fn reset_scope_and_indent() {
-    HIGHEST_SCOPE.store(0, Ordering::Relaxed);
-    INDENTATION_LEVEL.store(0, Ordering::Relaxed)
+    HIGHEST_SCOPE.set(0);
+    INDENTATION_LEVEL.set(0)
 }
 
+/// Returns the previous level
 pub fn change_indentation(change: IndentationChange) -> usize {
-    match change {
-        IndentationChange::More => INDENTATION_LEVEL.fetch_add(1, Ordering::Relaxed),
-        IndentationChange::Less => INDENTATION_LEVEL.fetch_sub(1, Ordering::Relaxed),
-    }
+    let level = INDENTATION_LEVEL.get();
+    INDENTATION_LEVEL.set(match change {
+        IndentationChange::More => level + 1,
+        IndentationChange::Less => level.saturating_sub(1),
+    });
+    level
 }
 
 pub type ScopeId = usize;
@@ -39,8 +41,77 @@ pub type ScopeId = usize;
 #[derive(Debug)]
 pub struct ProgramContext<'source> {
     pub symbols: SymbolTable<'source>,
-    pub types: HashMap<&'source str, typeexpr::Type>,
+    /// The scope each scope is nested in. The scope of the whole program has
+    /// no parent
+    pub scope_parents: HashMap<ScopeId, ScopeId>,
+    pub types: HashMap<&'source str, Type<'source>>,
+    pub type_docs: HashMap<&'source str, Vec<&'source str>>,
     pub file_context: FileContext<'source>,
+    /// Errors and warnings about the definitions, found while building the
+    /// context
+    pub diagnostics: Vec<Diagnostic>,
+    /// How many inlined calls are being emitted inside each other
+    pub inline_depth: Cell<usize>,
+    /// How many type predicates are being evaluated inside each other
+    pub predicate_depth: Cell<usize>,
+}
+
+impl<'source> ProgramContext<'source> {
+    pub fn new(file_context: FileContext<'source>) -> Self {
+        Self {
+            symbols: HashMap::new(),
+            scope_parents: HashMap::new(),
+            types: HashMap::new(),
+            type_docs: HashMap::new(),
+            file_context: FileContext {
+                line: 1,
+                ..file_context
+            },
+            diagnostics: vec![],
+            inline_depth: Cell::new(0),
+            predicate_depth: Cell::new(0),
+        }
+    }
+}
+
+impl<'source> ProgramContext<'source> {
+    /// `scope` and the scopes it is nested in, from the inside out
+    pub fn scopes_outward(&self, scope: ScopeId) -> impl Iterator<Item = ScopeId> + '_ {
+        std::iter::successors(Some(scope), |scope| self.scope_parents.get(scope).copied())
+    }
+
+    /// The symbols named `name` in the innermost scope around `scope` that
+    /// has any. They shadow the symbols of outer scopes
+    pub fn lookup(&self, name: &str, scope: ScopeId) -> Vec<&Symbol<'source>> {
+        let Some(symbols) = self.symbols.get(name) else {
+            return vec![];
+        };
+        self.scopes_outward(scope)
+            .map(|scope| {
+                symbols
+                    .iter()
+                    .filter(|symbol| symbol.scope == scope)
+                    .collect::<Vec<_>>()
+            })
+            .find(|symbols| !symbols.is_empty())
+            .unwrap_or_default()
+    }
+
+    /// Up to three names visible in `scope` that are spelled similar to
+    /// `name`, the closest first
+    pub fn similar_names(&self, name: &str, scope: ScopeId) -> Vec<&'source str> {
+        let scopes = self.scopes_outward(scope).collect::<Vec<_>>();
+        let max_distance = name.chars().count().div_ceil(3).max(1);
+        let mut names = self
+            .symbols
+            .iter()
+            .filter(|(_, symbols)| symbols.iter().any(|s| scopes.contains(&s.scope)))
+            .map(|(candidate, _)| (edit_distance(name, candidate), *candidate))
+            .filter(|(distance, _)| *distance <= max_distance)
+            .collect::<Vec<_>>();
+        names.sort_unstable();
+        names.into_iter().take(3).map(|(_, name)| name).collect()
+    }
 }
 
 pub type SymbolTable<'source> = HashMap<&'source str, Vec<Symbol<'source>>>;
@@ -49,4 +120,27 @@ pub type SymbolTable<'source> = HashMap<&'source str, Vec<Symbol<'source>>>;
 pub struct Symbol<'source> {
     pub scope: ScopeId,
     pub value: Rc<Expr<'source>>,
+    pub docs:  Vec<&'source str>,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::compile_to_string;
+
+    #[test]
+    fn lookup_shadows_outer_scopes() {
+        // The byte in the body of `g` shadows the outer `x`
+        let errors = compile_to_string("x = 2u16,\ng = fn { x = 1u8, x <- 1u16 },\n").unwrap_err();
+        assert_eq!(errors[0].code, Some("size-mismatch"));
+        assert_eq!(errors[0].message, "Size mismatch: can't move 2b into 1b");
+        assert!(compile_to_string("x = 2u16,\ng = fn { y = 1u8, x <- 1u16 },\n").is_ok());
+
+        // `h` is only visible in the body of `g`
+        let errors = compile_to_string("g = fn { h = fn {} },\nh,\n").unwrap_err();
+        assert_eq!(errors[0].code, Some("unknown-function"));
+        assert_eq!(errors[0].notes, vec![
+            "h is defined in another scope. Names defined inside a body are only visible in it"
+        ]);
+        assert_eq!(errors[0].help, vec!["Did you mean `g`?"]);
+    }
 }
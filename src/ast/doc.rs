@@ -0,0 +1,172 @@
+use std::io;
+
+use super::*;
+use crate::cli::DocFormat;
+
+/// Write the documentation page of a single program: every function with all
+/// of its overloads and every type, together with their doc comments
+pub fn write_docs<Output: io::Write>(
+    output: &mut Output,
+    title: &str,
+    ctx: &ProgramContext,
+    format: DocFormat,
+) -> io::Result<()> {
+    let mut function_names = ctx
+        .symbols
+        .iter()
+        .filter(|(_, symbols)| {
+            symbols
+                .iter()
+                .any(|symbol| matches!(symbol.value.as_ref(), Expr::FnDef(_)))
+        })
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>();
+    function_names.sort_unstable();
+
+    let mut type_names = ctx.types.keys().copied().collect::<Vec<_>>();
+    type_names.sort_unstable();
+
+    if format == DocFormat::Html {
+        writeln!(output, "<!DOCTYPE html>\n<html>\n<head>")?;
+        writeln!(output, "<meta charset=\"utf-8\">")?;
+        writeln!(output, "<title>{}</title>", escape_html(title))?;
+        writeln!(output, "</head>\n<body>")?;
+    }
+    heading(output, format, 1, title)?;
+
+    if !function_names.is_empty() {
+        heading(output, format, 2, "Functions")?;
+    }
+    for name in function_names {
+        heading(output, format, 3, name)?;
+        for symbol in &ctx.symbols[name] {
+            let Expr::FnDef(fn_def) = symbol.value.as_ref() else {
+                continue;
+            };
+            code(
+                output,
+                format,
+                &signature_to_source(ctx, name, &fn_def.signature),
+            )?;
+            docs(output, format, &symbol.docs)?;
+        }
+    }
+
+    if !type_names.is_empty() {
+        heading(output, format, 2, "Types")?;
+    }
+    for name in type_names {
+        heading(output, format, 3, name)?;
+        let type_ = &ctx.types[name];
+        let definition = match type_.predicate {
+            // The predicate is only readable as written
+            Some(_) => source_text(ctx, type_.span.clone()).to_string(),
+            None => type_.to_string(),
+        };
+        code(output, format, &format!("{name} = {definition}"))?;
+        docs(
+            output,
+            format,
+            ctx.type_docs.get(name).map_or(&[], Vec::as_slice),
+        )?;
+    }
+
+    if format == DocFormat::Html {
+        writeln!(output, "</body>\n</html>")?;
+    }
+    Ok(())
+}
+
+/// Turn a signature back into the syntax it was defined with, e.g.
+/// `turn = fn 3d [deg: Degrees]`. Constraints from a `where` clause are
+/// written into the parameters
+pub fn signature_to_source(ctx: &ProgramContext, name: &str, signature: &FnSignature) -> String {
+    let mut source = format!("{name} = fn");
+    for param in &signature.params {
+        source.push(' ');
+        match param {
+            // Literals are shown as written
+            Param::LiteralMatcher(raw_token) => source.push_str(source_text(ctx, raw_token.span())),
+            Param::ParamExpr(ParamExpr {
+                name, typenames, ..
+            }) => {
+                let types = typenames.join(" + ");
+                let param = match (name, typenames.is_empty()) {
+                    (Some(name), false) => format!("[{name}: {types}]"),
+                    (None, false) => format!("[:{types}]"),
+                    (Some(name), true) => format!("[{name}]"),
+                    (None, true) => "[]".to_string(),
+                };
+                source.push_str(&param);
+            }
+        }
+    }
+    source
+}
+
+fn heading<Output: io::Write>(
+    output: &mut Output,
+    format: DocFormat,
+    level: usize,
+    text: &str,
+) -> io::Result<()> {
+    match format {
+        DocFormat::Markdown => writeln!(output, "{} {}\n", "#".repeat(level), text),
+        DocFormat::Html => writeln!(output, "<h{level}>{}</h{level}>", escape_html(text)),
+    }
+}
+
+fn code<Output: io::Write>(output: &mut Output, format: DocFormat, code: &str) -> io::Result<()> {
+    match format {
+        DocFormat::Markdown => writeln!(output, "```exas\n{code}\n```\n"),
+        DocFormat::Html => writeln!(output, "<pre><code>{}</code></pre>", escape_html(code)),
+    }
+}
+
+fn docs<Output: io::Write>(
+    output: &mut Output,
+    format: DocFormat,
+    lines: &[&str],
+) -> io::Result<()> {
+    if lines.iter().all(|line| line.trim().is_empty()) {
+        return Ok(());
+    }
+    match format {
+        DocFormat::Markdown => writeln!(output, "{}\n", lines.join("\n").trim()),
+        DocFormat::Html => {
+            // Blank doc lines separate paragraphs
+            for paragraph in lines.split(|line| line.trim().is_empty()) {
+                if !paragraph.is_empty() {
+                    writeln!(output, "<p>{}</p>", escape_html(&paragraph.join("\n")))?;
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::compile_to_string;
+
+    #[test]
+    fn signatures_as_written() {
+        let source = concat!(
+            "T = type 2,\n",
+            "U = type 1..4,\n",
+            "f = fn 0x3 \"x\" [a] [:T] where a: T + U {},\n",
+            "f 3 \"x\",\n",
+        );
+        let errors = compile_to_string(source).unwrap_err();
+        assert_eq!(errors[0].notes, vec![
+            "`f = fn 0x3 \"x\" [a: T + U] [:T]` doesn't match:\ntakes 4 arguments, found 2"
+        ]);
+    }
+}
@@ -13,13 +13,37 @@ pub enum SmallValue {
 
 impl fmt::Display for SmallValue {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            SmallValue::Byte(val) => write!(f, "{:#02X}", val),
-            SmallValue::Word(val) => write!(f, "{:#04X}", val),
-            SmallValue::DWord(val) => write!(f, "{:#08X}", val),
-            SmallValue::QWord(val) | SmallValue::Untyped(val) => write!(f, "{:#016X}", val),
+        let size = match self {
+            SmallValue::Byte(_) => 1,
+            SmallValue::Word(_) => 2,
+            SmallValue::DWord(_) => 4,
+            SmallValue::QWord(_) | SmallValue::Untyped(_) => 8,
+        };
+        write!(f, "{}", self.to_hex(size))
+    }
+}
+
+impl SmallValue {
+    pub fn as_u64(&self) -> u64 {
+        match *self {
+            SmallValue::Byte(val) => val.into(),
+            SmallValue::Word(val) => val.into(),
+            SmallValue::DWord(val) => val.into(),
+            SmallValue::QWord(val) | SmallValue::Untyped(val) => val,
         }
     }
+
+    /// Two hexadecimal digits per byte of `size`, after the `0x`
+    pub fn to_hex(&self, size: usize) -> String {
+        format!("{:#0width$X}", self.as_u64(), width = 2 + 2 * size)
+    }
+
+    /// The smallest number of bytes the value fits into
+    pub fn min_bytes(&self) -> usize {
+        (u64::BITS - self.as_u64().leading_zeros())
+            .div_ceil(8)
+            .max(1) as usize
+    }
 }
 
 impl CompTimeSize<'_> for SmallValue {
@@ -29,23 +53,80 @@ impl CompTimeSize<'_> for SmallValue {
             Self::Word(_) => ByteSize::Exact(2),
             Self::DWord(_) => ByteSize::Exact(4),
             Self::QWord(_) => ByteSize::Exact(8),
-            Self::Untyped(_) => ByteSize::Range(1..8),
+            Self::Untyped(_) => ByteSize::Range(1..=8),
         }
     }
 }
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone)]
 pub enum Expr<'source> {
     FnDef(FnDef<'source>),
     FnCall(FnCall<'source>),
-    Type(typeexpr::Type),
+    Type(Type<'source>),
 
     Assign(Assign<'source>),
+    Move(Move<'source>),
+
+    SmallValue(SmallValue, Span),
+    Register(Register, Span),
+    /// A function parameter used in the body
+    Local(Local<'source>, Span),
+
+    /// Decoded string literal
+    Bytes(Box<[u8]>, Span),
+    FormatString(FormatString<'source>),
+
+    List(List<'source>),
+}
+
+impl Expr<'_> {
+    pub fn span(&self) -> Span {
+        match self {
+            Expr::FnDef(fn_def) => fn_def.span.clone(),
+            Expr::FnCall(fn_call) => fn_call.span.clone(),
+            Expr::Type(type_) => type_.span.clone(),
+            Expr::Assign(assign) => assign.span.clone(),
+            Expr::Move(move_) => move_.span.clone(),
+            Expr::FormatString(format_string) => format_string.span.clone(),
+            Expr::List(list) => list.span.clone(),
+            Expr::SmallValue(_, span)
+            | Expr::Register(_, span)
+            | Expr::Local(_, span)
+            | Expr::Bytes(_, span) => span.clone(),
+        }
+    }
 
-    SmallValue(SmallValue),
+    /// Whether the expression stands for a value that can be passed to a
+    /// function. Calls don't return anything yet, and blocks are no values
+    pub fn is_value(&self) -> bool {
+        match self {
+            Expr::List(list) => list.is_data(),
+            _ => matches!(
+                self,
+                Expr::SmallValue(..) | Expr::Register(..) | Expr::Local(..) | Expr::Bytes(..)
+            ),
+        }
+    }
+}
 
-    Bytes(Box<[u8]>),
-    StringSlice(&'source str),
+impl PartialEq for Expr<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        // Ignore spans
+        match (self, other) {
+            (Expr::FnDef(a), Expr::FnDef(b)) => a == b,
+            (Expr::FnCall(a), Expr::FnCall(b)) => a == b,
+            (Expr::Type(a), Expr::Type(b)) => a == b,
+            (Expr::Assign(a), Expr::Assign(b)) => a == b,
+            (Expr::Move(a), Expr::Move(b)) => a == b,
+            (Expr::SmallValue(a, _), Expr::SmallValue(b, _)) => a == b,
+            (Expr::Register(a, _), Expr::Register(b, _)) => a == b,
+            (Expr::Local(a, _), Expr::Local(b, _)) => a == b,
+            (Expr::Bytes(a, _), Expr::Bytes(b, _)) => a == b,
+            (Expr::FormatString(a), Expr::FormatString(b)) => a == b,
+            (Expr::List(a), Expr::List(b)) => a == b,
+            _ => false,
+        }
+    }
 }
 
 impl std::fmt::Debug for Expr<'_> {
@@ -55,15 +136,29 @@ impl std::fmt::Debug for Expr<'_> {
             Expr::FnCall(fn_call) => fn_call.fmt(f),
             Expr::Type(type_) => type_.fmt(f),
             Expr::Assign(assign) => assign.fmt(f),
-            Expr::SmallValue(value) => write!(f, "{:?}", value),
-            Expr::Bytes(bytes) => {
+            Expr::Move(move_) => move_.fmt(f),
+            Expr::SmallValue(value, _) => write!(f, "{:?}", value),
+            Expr::Register(register, _) => write!(f, "{register}"),
+            Expr::Local(local, _) => write!(f, "{}", local.location),
+            Expr::Bytes(bytes, _) => {
                 write!(f, "0x")?;
                 for byte in bytes.iter() {
                     write!(f, "{:02X}", byte)?;
                 }
                 Ok(())
             }
-            Expr::StringSlice(string) => write!(f, "{string}"),
+            Expr::FormatString(format_string) => format_string.fmt(f),
+            Expr::List(list) => {
+                let (open, close) = if list.mutable { ("[", "]") } else { ("{", "}") };
+                write!(f, "{open}")?;
+                for (i, element) in list.elements.elements.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{element:?}")?;
+                }
+                write!(f, "{close}")
+            }
         }
     }
 }
@@ -71,13 +166,17 @@ impl std::fmt::Debug for Expr<'_> {
 impl CompTimeSize<'_> for Expr<'_> {
     fn number_bytes(&self, ctx: &ProgramContext) -> ByteSize {
         match self {
-            Self::FnDef(_) => unreachable!(),
-            Self::FnCall(_) => todo!(), // fn_call.number_bytes(),
+            // Not values, they are rejected where values are expected
+            Self::FnDef(_) | Self::FnCall(_) | Self::Assign(_) => ByteSize::AnySize,
             Self::Type(_) => ByteSize::Exact(0),
-            Self::Assign(_) => todo!(), // assign.number_bytes(),
-            Self::SmallValue(value) => value.number_bytes(ctx),
-            Self::Bytes(bytes) => ByteSize::Exact(bytes.len()),
-            Self::StringSlice(slice) => ByteSize::Exact(slice.bytes().len()),
+            Self::Move(_) => ByteSize::Exact(0),
+            Self::SmallValue(value, _) => value.number_bytes(ctx),
+            Self::Register(register, _) => register.number_bytes(ctx),
+            Self::Local(local, _) => local.number_bytes(ctx),
+            Self::Bytes(bytes, _) => ByteSize::Exact(bytes.len()),
+            // Calls are expanded per segment before the size is needed
+            Self::FormatString(_) => ByteSize::AnySize,
+            Self::List(list) => list.number_bytes(ctx),
         }
     }
 }
@@ -87,11 +186,15 @@ impl<'source> AstNode<'source> for Expr<'source> {
         match self {
             Expr::FnDef(fn_def) => fn_def.build_context(ctx, scope_stack),
             Expr::Assign(assign) => assign.build_context(ctx, scope_stack),
+            Expr::List(list) => list.build_context(ctx, scope_stack),
+            Expr::FnCall(fn_call) => fn_call.build_context(ctx, scope_stack),
             Expr::Type(_)
-            | Expr::FnCall(_)
-            | Expr::SmallValue(_)
-            | Expr::Bytes(_)
-            | Expr::StringSlice(_) => {}
+            | Expr::Move(_)
+            | Expr::SmallValue(..)
+            | Expr::Register(..)
+            | Expr::Local(..)
+            | Expr::Bytes(..)
+            | Expr::FormatString(_) => {}
         }
     }
 
@@ -104,10 +207,24 @@ impl<'source> AstNode<'source> for Expr<'source> {
         match self {
             Expr::FnDef(fn_def) => fn_def.check_and_emit(output, ctx, scope_stack)?,
             Expr::Assign(assign) => assign.check_and_emit(output, ctx, scope_stack)?,
+            Expr::Move(move_) => move_.check_and_emit(output, ctx, scope_stack)?,
+            Expr::List(list) => list.check_and_emit(output, ctx, scope_stack)?,
             Expr::Type(type_) => write!(output, "{}Type({:?})", current_padding(), type_)?,
-            Expr::StringSlice(string) => write!(output, "{}String({})", current_padding(), string)?,
-            Expr::Bytes(bytes) => write!(output, "{}Bytes({:?})", current_padding(), bytes)?,
-            Expr::SmallValue(value) => write!(output, "{}Value({})", current_padding(), value)?,
+            Expr::Bytes(bytes, _) => write!(output, "{}Bytes({:?})", current_padding(), bytes)?,
+            Expr::FormatString(format_string) => write!(
+                output,
+                "{}FormatString({:?})",
+                current_padding(),
+                format_string
+            )?,
+            Expr::SmallValue(value, _) => write!(output, "{}Value({})", current_padding(), value)?,
+            Expr::Register(register, _)
+            | Expr::Local(
+                Local {
+                    location: register, ..
+                },
+                _,
+            ) => write!(output, "{}Register({})", current_padding(), register)?,
             Expr::FnCall(fn_call) => fn_call.check_and_emit(output, ctx, scope_stack)?,
         }
         Ok(())
@@ -117,77 +234,143 @@ impl<'source> AstNode<'source> for Expr<'source> {
 impl<'source> Parsable<'source> for Expr<'source> {
     /// Should be called when on the first token
     fn parse(parser: &mut Parser<'source>) -> Result<Expr<'source>, ParsingError<'source>> {
-        let token = match parser.current_token.as_ref() {
-            Some(Ok(token)) => token,
-            Some(Err(())) => {
-                return Err(ParsingError::TokenError(format!(
-                    "Lexer error in {file}@{line}",
-                    file = parser.lexer.extras.filename,
-                    line = parser.lexer.extras.line
-                )))
-            }
-            None => {
-                return Err(ParsingError::AbruptEof(
+        let mut expr = parse_operand(parser)?;
+        while let Some(Ok(Token::RightArrow)) = parser.current_token {
+            expr = parse_move_from(parser, expr)?;
+        }
+        Ok(expr)
+    }
+}
+
+/// An expression without trailing `-> destination`s
+fn parse_operand<'source>(
+    parser: &mut Parser<'source>,
+) -> Result<Expr<'source>, ParsingError<'source>> {
+    let token = match parser.current_token.as_ref() {
+        Some(Ok(token)) => token,
+        Some(Err(err)) => {
+            return Err(ParsingError::TokenError(
+                err.clone(),
+                parser.lexer.extras.clone(),
+                parser.current_span.clone(),
+            ))
+        }
+        None => {
+            return Err(ParsingError::AbruptEof(
+                "expr",
+                parser.lexer.extras.clone(),
+                parser.current_span.clone(),
+                vec![
+                    Token::Symbol("("),
+                    Token::Ident,
+                    Token::Int(SmallValue::Untyped(0)),
+                    Token::String(Box::default()),
+                    Token::KeywordFn,
+                    Token::Symbol("{"),
+                    Token::Symbol("["),
+                ],
+            ))
+        }
+    };
+    let start = parser.current_span.start;
+    match token {
+        Token::Symbol("(") => {
+            parser.advance();
+            let expr = Expr::parse(parser)?;
+            match parser.current_token {
+                Some(Ok(Token::Symbol(")"))) => {
+                    parser.advance();
+                    Ok(expr)
+                }
+                Some(Ok(ref token)) => Err(ParsingError::unexpected_token(
                     "expr",
                     parser.lexer.extras.clone(),
-                    vec![
-                        Token::Symbol("("),
-                        Token::Ident,
-                        Token::Int(0),
-                        Token::String,
-                        Token::KeywordFn,
-                    ],
-                ))
-            }
-        };
-        match token {
-            Token::Symbol("(") => {
-                parser.advance();
-                Expr::parse(parser)
+                    parser.current_span.clone(),
+                    token.clone(),
+                    vec![Token::Symbol(")")],
+                )),
+                Some(Err(ref err)) => Err(ParsingError::TokenError(
+                    err.clone(),
+                    parser.lexer.extras.clone(),
+                    parser.current_span.clone(),
+                )),
+                None => Err(ParsingError::AbruptEof(
+                    "expr",
+                    parser.lexer.extras.clone(),
+                    parser.current_span.clone(),
+                    vec![Token::Symbol(")")],
+                )),
             }
-            Token::Ident => {
-                let ident = parser.current_slice;
-                parser.advance();
-                match parser.current_token {
-                    Some(Ok(Token::Symbol("="))) => {
-                        parser.advance();
-                        Ok(parse_assign(parser, ident)?)
-                    }
-                    None | Some(Ok(_)) => Ok(Expr::FnCall(FnCall {
+        }
+        Token::Ident => {
+            let ident = parser.current_slice;
+            let ident_span = parser.current_span.clone();
+            parser.advance();
+            match parser.current_token {
+                Some(Ok(Token::Symbol("="))) => {
+                    parser.advance();
+                    Ok(parse_assign(parser, ident, start)?)
+                }
+                Some(Ok(Token::LeftArrow)) => {
+                    parser.advance();
+                    parse_move_into(parser, Destination::Alias(ident, ident_span), start)
+                }
+                None | Some(Ok(_)) => {
+                    let args = ArgumentList::parse(parser)?;
+                    // Don't include the ',' that ends the argument list
+                    let end = args.last().map_or(ident_span.end, |arg| arg.span().end);
+                    Ok(Expr::FnCall(FnCall {
                         name: ident,
-                        args: ArgumentList::parse(parser)?,
-                    })),
-                    Some(Err(())) => Err(ParsingError::TokenError(format!(
-                        "Lexer error in {file}@{line}",
-                        file = parser.lexer.extras.filename,
-                        line = parser.lexer.extras.line,
-                    ))),
+                        args,
+                        span: start..end,
+                    }))
                 }
+                Some(Err(ref err)) => Err(ParsingError::TokenError(
+                    err.clone(),
+                    parser.lexer.extras.clone(),
+                    parser.current_span.clone(),
+                )),
             }
-            Token::Int(val) => {
-                let val = *val;
-                parser.advance();
-                Ok(Expr::SmallValue(SmallValue::Untyped(val.into())))
-            }
-            Token::String => {
-                let strval = parser.current_slice;
-                parser.advance();
-                Ok(Expr::StringSlice(strval))
-            }
-            Token::KeywordFn => {
-                parser.advance();
-                Ok(Expr::FnDef(FnDef::parse(parser)?))
-            }
-            Token::KeywordType => {
-                parser.advance();
-                Ok(Expr::Type(typeexpr::Type::parse(parser)?))
+        }
+        Token::Int(val) => {
+            let val = val.clone();
+            parser.advance();
+            Ok(Expr::SmallValue(val, parser.span_from(start)))
+        }
+        Token::Reg(number) => {
+            let register = Register(*number);
+            parser.advance();
+            let span = parser.span_from(start);
+            match parser.current_token {
+                Some(Ok(Token::LeftArrow)) => {
+                    parser.advance();
+                    parse_move_into(parser, Destination::Register(register, span), start)
+                }
+                _ => Ok(Expr::Register(register, span)),
             }
-            _ => Err(ParsingError::UnexpectedToken(
-                "expr",
-                parser.lexer.extras.clone(),
-                token.clone(),
-                vec![Token::Ident, Token::Int(0), Token::String, Token::KeywordFn],
-            )),
         }
+        Token::String(bytes) => {
+            let bytes = bytes.clone();
+            parser.advance();
+            Ok(Expr::Bytes(bytes, parser.span_from(start)))
+        }
+        Token::FormatString(_) => Ok(Expr::FormatString(FormatString::parse(parser)?)),
+        Token::Symbol("{" | "[") => Ok(Expr::List(List::parse(parser)?)),
+        Token::KeywordFn => Ok(Expr::FnDef(FnDef::parse(parser)?)),
+        Token::KeywordType => Ok(Expr::Type(Type::parse(parser)?)),
+        _ => Err(ParsingError::unexpected_token(
+            "expr",
+            parser.lexer.extras.clone(),
+            parser.current_span.clone(),
+            token.clone(),
+            vec![
+                Token::Ident,
+                Token::Int(SmallValue::Untyped(0)),
+                Token::String(Box::default()),
+                Token::KeywordFn,
+                Token::Symbol("{"),
+                Token::Symbol("["),
+            ],
+        )),
     }
 }
@@ -11,13 +11,26 @@ impl<'source> Parsable<'source> for ArgumentList<'source> {
                     parser.advance();
                     break;
                 }
+                // The call is the source of a move
+                Token::LeftArrow | Token::RightArrow => break,
+                // The end of the list or parentheses the call is in
+                Token::Symbol("}" | "]") if parser.list_depth > 1 => break,
+                Token::Symbol(")") => break,
+                Token::Symbol("{" | "[") => args.push(RawToken::Expr(Expr::parse(parser)?)),
                 Token::Symbol("(") => {
                     parser.advance(); // Consume the '('
                     args.push(RawToken::Expr(Expr::parse(parser)?)); // Parse the expression
                     parser.advance(); // Consume the ')'
                 }
+                Token::FormatString(_) => args.push(RawToken::Expr(Expr::FormatString(
+                    FormatString::parse(parser)?,
+                ))),
                 token => {
-                    args.push(RawToken::from_token(token, parser.current_slice));
+                    args.push(RawToken::from_token(
+                        token,
+                        parser.current_slice,
+                        parser.current_span.clone(),
+                    ));
                     parser.advance();
                 } // TODO: Some other tokens are not allowed here?
             }
@@ -1,90 +1,207 @@
 use super::*;
 use crate::errors::compile_error;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone)]
 pub struct FnCall<'source> {
     pub name: &'source str,
     pub args: Vec<RawToken<'source>>,
+    pub span: Span,
 }
 
-impl AstNode<'_> for FnCall<'_> {
-    fn build_context(&self, _ctx: &mut ProgramContext, _scope_stack: &mut Vec<ScopeId>) {}
+impl PartialEq for FnCall<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        // Ignore span
+        self.name == other.name && self.args == other.args
+    }
+}
 
-    fn check_and_emit<Output: std::io::Write>(
+impl<'source> FnCall<'source> {
+    /// The arguments with `(name)` replaced by the parameter `name` refers to
+    fn args_with_locals(
         &self,
-        output: &mut Output,
-        ctx: &ProgramContext<'_>,
-        scope_stack: &mut Vec<ScopeId>,
-    ) -> CheckResult<()> {
-        // Find all symbols with that name
-        let Some(global_matches) = ctx.symbols.get(self.name) else {
-            return compile_error(
-                ctx.file_context.clone(),
-                format!("Function not found anywhere: {}", self.name).to_string(),
-            );
-        };
-        // Filter out functions that are not in scope
-        let scope_matches = global_matches
+        ctx: &ProgramContext<'source>,
+        scope: ScopeId,
+    ) -> Vec<RawToken<'source>> {
+        self.args
             .iter()
-            .filter(|f| scope_stack.contains(&f.scope));
-        if scope_matches.clone().next().is_none() {
-            return compile_error(
-                ctx.file_context.clone(),
-                format!(
-                    "Function not defined in this scope: {}, scope: {}",
-                    self.name,
-                    scope_stack.last().unwrap()
-                ),
-            );
+            .map(|arg| match arg {
+                RawToken::Expr(Expr::FnCall(FnCall { name, args, span })) if args.is_empty() => {
+                    match ctx
+                        .lookup(name, scope)
+                        .last()
+                        .map(|symbol| symbol.value.as_ref())
+                    {
+                        Some(Expr::Local(local, _)) => {
+                            RawToken::Expr(Expr::Local(local.clone(), span.clone()))
+                        }
+                        _ => arg.clone(),
+                    }
+                }
+                _ => arg.clone(),
+            })
+            .collect()
+    }
+
+    /// The overloads of the called function in the innermost scope around
+    /// `scope` that has any symbol with its name
+    fn find_functions<'ctx>(
+        &self,
+        ctx: &'ctx ProgramContext<'source>,
+        scope: ScopeId,
+    ) -> CheckResult<Vec<&'ctx FnDef<'source>>> {
+        let scope_matches = ctx.lookup(self.name, scope);
+        if scope_matches.is_empty() {
+            let mut diagnostic = Diagnostic::error(
+                self.span.clone(),
+                format!("No function named {} in scope", self.name),
+            )
+            .with_code("unknown-function")
+            .with_primary_label("not found in this scope");
+            if ctx.symbols.contains_key(self.name) {
+                diagnostic = diagnostic.with_note(format!(
+                    "{} is defined in another scope. Names defined inside a body are only visible \
+                     in it",
+                    self.name
+                ));
+            }
+            let similar_names = ctx
+                .similar_names(self.name, scope)
+                .iter()
+                .map(|name| format!("`{name}`"))
+                .collect::<Vec<_>>();
+            match similar_names.as_slice() {
+                [] => {}
+                [name] => diagnostic = diagnostic.with_help(format!("Did you mean {name}?")),
+                names => {
+                    diagnostic =
+                        diagnostic.with_help(format!("Did you mean one of {}?", names.join(", ")))
+                }
+            }
+            return Err(diagnostic.into());
         }
         // Retain only functions
-        let function_matches = scope_matches.filter_map(|f| match f.value.as_ref() {
-            Expr::FnDef(fn_def) => Some(fn_def),
-            _ => None,
-        });
-        let Some(first_fn_match) = function_matches.clone().next() else {
+        let function_matches = scope_matches
+            .iter()
+            .filter_map(|f| match f.value.as_ref() {
+                Expr::FnDef(fn_def) => Some(fn_def),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        if function_matches.is_empty() {
             return compile_error(
-                ctx.file_context.clone(),
+                self.span.clone(),
+                "not-callable",
                 format!(
                     "Function not found {name}: {name} exists in this scope, but is not callable",
                     name = self.name
                 )
                 .to_string(),
             );
-        };
+        }
+        Ok(function_matches)
+    }
+}
+
+impl<'source> AstNode<'source> for FnCall<'source> {
+    fn build_context(&self, ctx: &mut ProgramContext<'source>, scope_stack: &mut Vec<ScopeId>) {
+        // Blocks passed as arguments are nested in the scope of the call
+        for arg in &self.args {
+            if let RawToken::Expr(expr) = arg {
+                expr.build_context(ctx, scope_stack);
+            }
+        }
+    }
+
+    fn check_and_emit<Output: std::io::Write>(
+        &self,
+        output: &mut Output,
+        ctx: &ProgramContext<'_>,
+        scope_stack: &mut Vec<ScopeId>,
+    ) -> CheckResult<()> {
+        // Find the symbols with that name in the innermost scope that has any
+        let scope = *scope_stack.last().unwrap();
+        let function_matches = self.find_functions(ctx, scope)?;
+
+        // Expand a format string argument into one call per segment
+        let format_string = self.args.iter().enumerate().find_map(|(i, arg)| match arg {
+            RawToken::Expr(Expr::FormatString(format_string)) => Some((i, format_string)),
+            _ => None,
+        });
+        if let Some((index, format_string)) = format_string {
+            for segment in &format_string.segments {
+                segment.check_names(ctx, scope)?;
+            }
+            for segment in &format_string.segments {
+                let mut args = self.args.clone();
+                args[index] = segment.to_arg();
+                FnCall {
+                    name: self.name,
+                    args,
+                    span: self.span.clone(),
+                }
+                .check_and_emit(output, ctx, scope_stack)?;
+            }
+            return Ok(());
+        }
+
+        let args = self.args_with_locals(ctx, scope);
         // Check signature
-        let mut signature_matches =
-            function_matches.filter(|f| f.signature.matches_args(ctx, &self.args));
-        let Some(fn_def) = signature_matches.next() else {
-            return compile_error(
-                ctx.file_context.clone(),
-                format!(
-                    "Function signature mismatch: \"{name}\"\nArgs {actual:#?} don't match to any \
-                     signature.\n\nNote: One candidate is \"{name}\" with \
-                     signature:\n{expected:#?}",
-                    name = self.name,
-                    actual = self.args,
-                    expected = first_fn_match.signature
-                )
-                .to_string(),
-            );
+        let signature_matches = function_matches
+            .iter()
+            .filter(|f| f.signature.matches_args(ctx, &args))
+            .collect::<Vec<_>>();
+        if signature_matches.is_empty() {
+            let mut diagnostic = Diagnostic::error(
+                self.span.clone(),
+                format!("No overload of {} matches the arguments", self.name),
+            )
+            .with_code("signature-mismatch")
+            .with_primary_label("no overload matches these arguments");
+            for fn_def in function_matches {
+                diagnostic = diagnostic
+                    .with_label(fn_def.span.clone(), "this overload doesn't match")
+                    .with_note(format!(
+                        "`{}` doesn't match:\n{}",
+                        signature_to_source(ctx, self.name, &fn_def.signature),
+                        fn_def.signature.mismatches(ctx, &args).join("\n")
+                    ));
+            }
+            return Err(diagnostic.into());
+        }
+        // Call the most specific overload
+        let mut most_specific = signature_matches.iter().filter(|fn_def| {
+            !signature_matches.iter().any(|other| {
+                other
+                    .signature
+                    .is_more_specific_than(&fn_def.signature, ctx)
+            })
+        });
+        let Some(fn_def) = most_specific.next() else {
+            unreachable!("Some overload is not less specific than any other")
         };
-        // Should only have one match
-        if let Some(second_fn_def) = signature_matches.next() {
-            return compile_error(
-                ctx.file_context.clone(),
+        // Should only have one
+        if let Some(second_fn_def) = most_specific.next() {
+            let args_text = args
+                .iter()
+                .map(|arg| format!("`{}`", source_text(ctx, arg.span())))
+                .collect::<Vec<_>>()
+                .join(" ");
+            return Err(Diagnostic::error(
+                self.span.clone(),
                 format!(
-                    "Found two matching functions for \"{name}\" with the given \
-                     arguments:\n\nArguments: {args:?}\n\n| The following function definitions \
-                     match:\n{fn_sig1:#?}\n| as well as:\n{fn_sig2:#?}\n| Consider adding a ident \
-                     to the function signature to distinguish them.",
-                    name = self.name,
-                    args = self.args,
-                    fn_sig1 = fn_def.signature,
-                    fn_sig2 = second_fn_def.signature,
-                )
-                .to_string(),
-            );
+                    "Found two matching functions for \"{}\" with the given arguments",
+                    self.name
+                ),
+            )
+            .with_code("ambiguous-call")
+            .with_primary_label(format!("both match the arguments {args_text}"))
+            .with_label(fn_def.span.clone(), "this definition matches")
+            .with_label(
+                second_fn_def.span.clone(),
+                "as well as this one, which is just as specific",
+            )
+            .with_help("Consider adding an ident to the function signature to distinguish them")
+            .into());
         }
         // How do I find the number of bytes?
         // Either the args or the signature have the specific size
@@ -98,13 +215,23 @@ impl AstNode<'_> for FnCall<'_> {
         //
         // @see ByteSize::overlap(..)
         //
+        if fn_def.signature.is_inlined(ctx) {
+            writeln!(
+                output,
+                "\n{pad}| Inlined call: {name}",
+                name = self.name,
+                pad = current_padding()
+            )?;
+            let sizes = push_args(output, ctx, &args, &fn_def.signature.params)?;
+            return fn_def.emit_inline(output, ctx, scope_stack, &args, &sizes, self.span.clone());
+        }
         writeln!(
             output,
             "\n{pad}| Function call: {name}",
             name = self.name,
             pad = current_padding()
         )?;
-        push_args(output, ctx, &self.args, &fn_def.signature.params)?;
+        push_args(output, ctx, &args, &fn_def.signature.params)?;
         writeln!(output, "{}call {}", current_padding(), self.name)?;
         Ok(())
     }
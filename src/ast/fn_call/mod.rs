@@ -1,5 +1,6 @@
 pub mod argument_list;
 
+#[allow(clippy::module_inception)]
 pub mod fn_call;
 
 mod push_args;
@@ -8,4 +9,4 @@ pub use push_args::push_args;
 mod resolve_arg_size;
 pub use resolve_arg_size::resolve_arg_size;
 
-pub(self) use super::*;
+use super::*;
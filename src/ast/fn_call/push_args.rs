@@ -2,36 +2,54 @@ use std::io;
 
 use super::*;
 
+/// Push the arguments that are passed on the stack, the last one first.
+/// Returns the sizes they were pushed with, in the order of the parameters
 pub fn push_args<Output: io::Write>(
     output: &mut Output,
     ctx: &ProgramContext,
     args: &[RawToken],
     params: &[Param],
-) -> CheckResult<()> {
+) -> CheckResult<Vec<usize>> {
     let number_bytes = params
         .iter()
         .zip(args)
         .rev()
         .filter_map(|(param, arg)| match &param {
             Param::LiteralMatcher(_) => None,
-            Param::ParamExpr(ParamExpr { typename, .. }) => {
-                let Some(typename) = typename else {
-                    todo!("Type inference and generics not yet implemented");
-                };
-                let type_ = find_type(ctx, typename).unwrap();
-                let size = match resolve_arg_size(ctx, type_, arg) {
+            // Blocks are inlined instead
+            Param::ParamExpr(param) if param.is_comptime() => None,
+            Param::ParamExpr(param) => {
+                // TODO: Type inference and generics
+                if param.typenames.is_empty() {
+                    let name = param
+                        .name
+                        .map_or(String::new(), |name| format!(" `{name}`"));
+                    return Some(Err(Diagnostic::error(
+                        arg.span(),
+                        format!("Can't pass an argument to the untyped parameter{name}"),
+                    )
+                    .with_code("unknown-size")
+                    .with_primary_label("the size this is passed with isn't known")
+                    .with_label(param.span.clone(), "this parameter has no type")
+                    .with_help("Give the parameter a type, like `[name: Type]`")
+                    .into()));
+                }
+                if let Err(err) = param.check_types(ctx) {
+                    return Some(Err(err));
+                }
+                let size = match resolve_arg_size(ctx, param, arg) {
                     Ok(size) => size,
                     Err(err) => return Some(Err(err)),
                 };
                 Some(Ok((size, arg)))
             }
         });
-    // TODO: Properly handle errors
-    for e in number_bytes {
-        let Ok((size, arg)) = e else {
-            return Err(e.unwrap_err());
-        };
+    let mut sizes = vec![];
+    for pushed in number_bytes {
+        let (size, arg) = pushed?;
         writeln!(output, "push {}b {:?}", size, arg)?;
+        sizes.push(size);
     }
-    Ok(())
+    sizes.reverse();
+    Ok(sizes)
 }
@@ -1,21 +1,57 @@
 use super::*;
 
+/// The number of bytes `arg` is passed with to `param`: the `passing_size` of
+/// the parameter, or the smallest size of its range that fits the argument.
+/// Reports arguments that don't fit
 pub fn resolve_arg_size(
     ctx: &ProgramContext,
-    type_: &typeexpr::Type,
+    param: &ParamExpr,
     arg: &RawToken,
 ) -> Result<usize, CheckError> {
-    let type_size = &type_.size;
-    let arg_size = &arg.number_bytes(ctx);
-
-    match type_size.overlap(arg_size) {
-        None => compile_error(
-            ctx.file_context.clone(),
-            format!("Type size mismatch: no overlap between {type_size:?} and {arg_size:?}")
-                .to_string(),
-        ),
-        Some(ByteSize::Exact(size)) => Ok(size),
-        Some(ByteSize::Range(_)) => todo!("Default size for type?"),
-        Some(ByteSize::AnySize) => unreachable!("wtf should I do here"),
+    let arg_size = arg.number_bytes(ctx);
+    // The fewest bytes the argument needs, and the most it can fill. Untyped
+    // literals only take the space their value needs
+    let (needed, fitting) = match arg {
+        RawToken::Expr(Expr::SmallValue(value @ SmallValue::Untyped(_), _)) => {
+            (Some(value.min_bytes()), Some(value.min_bytes()))
+        }
+        _ => (arg_size.smallest(), arg_size.largest()),
+    };
+    let (size, largest) = match (param.passing_size(ctx), param.allowed_size(ctx)) {
+        (Some(size), _) => (size, size),
+        (None, Some(ByteSize::Range(range))) => {
+            let size = fitting.map_or(*range.end(), |fitting| {
+                fitting.clamp(*range.start(), *range.end())
+            });
+            (size, *range.end())
+        }
+        _ => {
+            return Err(Diagnostic::error(
+                arg.span(),
+                "The size this argument is passed with isn't known".to_string(),
+            )
+            .with_code("size-mismatch")
+            .with_label(
+                param.span.clone(),
+                "the types of this parameter allow any size",
+            )
+            .with_help("Give the parameter a type with a size, like `[name: u8]`")
+            .into())
+        }
+    };
+    // Smaller arguments are widened
+    match needed {
+        Some(needed) if needed > largest => Err(Diagnostic::error(
+            arg.span(),
+            format!("Value doesn't fit into {largest}b"),
+        )
+        .with_code("size-mismatch")
+        .with_primary_label(format!("needs at least {needed}b"))
+        .with_label(
+            param.span.clone(),
+            format!("arguments are passed with at most {largest}b"),
+        )
+        .into()),
+        _ => Ok(size),
     }
 }
@@ -1,13 +1,41 @@
+use std::rc::Rc;
+
 use super::*;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone)]
 pub struct FnDef<'source> {
     pub signature: FnSignature<'source>,
     pub body:      ListContent<'source>,
+    pub docs:      Vec<&'source str>,
+    pub span:      Span,
+}
+
+impl PartialEq for FnDef<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        // Ignore docs and span
+        self.signature == other.signature && self.body == other.body
+    }
 }
 
 impl<'source> AstNode<'source> for FnDef<'source> {
     fn build_context(&self, ctx: &mut ProgramContext<'source>, scope_stack: &mut Vec<ScopeId>) {
+        // Parameters are names in the body
+        for (param, location) in self.signature.param_locations() {
+            let Some(name) = param.name else {
+                continue;
+            };
+            ctx.symbols.entry(name).or_default().push(Symbol {
+                scope: self.body.scope,
+                value: Rc::new(Expr::Local(
+                    Local {
+                        param: param.clone(),
+                        location,
+                    },
+                    param.span.clone(),
+                )),
+                docs:  vec![],
+            });
+        }
         self.body.build_context(ctx, scope_stack);
     }
 
@@ -17,21 +45,152 @@ impl<'source> AstNode<'source> for FnDef<'source> {
         ctx: &ProgramContext,
         scope_stack: &mut Vec<ScopeId>,
     ) -> CheckResult<()> {
+        for param in &self.signature.params {
+            if let Param::ParamExpr(param) = param {
+                param.check_types(ctx)?;
+            }
+        }
+        if self.signature.is_inlined(ctx) {
+            // Only check the body with empty blocks, it is emitted at every
+            // call instead
+            writeln!(output, "{}| Inlined at every call", current_padding())?;
+            let has_meta_params = self.signature.params.iter().any(|param| {
+                matches!(param, Param::ParamExpr(param) if param.meta_types().next().is_some())
+            });
+            if has_meta_params {
+                // The body means nothing without the tokens
+                return Ok(());
+            }
+            let empty = RawToken::Expr(Expr::List(List {
+                mutable:  false,
+                elements: ListContent {
+                    elements: vec![],
+                    scope:    self.body.scope,
+                },
+                span:     self.span.clone(),
+            }));
+            let blocks = self
+                .signature
+                .params
+                .iter()
+                .filter_map(|param| match param {
+                    Param::ParamExpr(
+                        param @ ParamExpr {
+                            name: Some(name), ..
+                        },
+                    ) if param.is_block() => Some((*name, &empty)),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            let body = ListContent {
+                elements: inline_args(&self.body.elements, &blocks),
+                scope:    self.body.scope,
+            };
+            return body.check_and_emit(&mut std::io::sink(), ctx, scope_stack);
+        }
         // TODO: pass first parameters through registers
-        stack_pop_remaining_parameters(ctx, output, &self.signature.params)?;
+        let sizes = passing_sizes(ctx, &self.signature)?;
+        stack_pop_remaining_parameters(output, &self.signature, &sizes)?;
         self.body.check_and_emit(output, ctx, scope_stack)?;
         writeln!(output, "{}ret", current_padding())?;
         Ok(())
     }
 }
 
+/// How deep inlined calls can be nested before they are taken to be recursive
+const MAX_INLINE_DEPTH: usize = 64;
+
+impl<'source> FnDef<'source> {
+    /// Emit the body at the call site, with the blocks and tokens in `args` in
+    /// place of the block and meta type parameters. The other arguments have
+    /// to be pushed already
+    pub fn emit_inline<Output: std::io::Write>(
+        &self,
+        output: &mut Output,
+        ctx: &ProgramContext,
+        scope_stack: &mut Vec<ScopeId>,
+        args: &[RawToken<'source>],
+        sizes: &[usize],
+        call_span: Span,
+    ) -> CheckResult<()> {
+        let depth = ctx.inline_depth.get();
+        if depth >= MAX_INLINE_DEPTH {
+            return Err(
+                Diagnostic::error(call_span, "Recursive inline call".to_string())
+                    .with_code("recursive-inline")
+                    .with_primary_label(format!("inlined more than {MAX_INLINE_DEPTH} calls deep"))
+                    .with_label(self.span.clone(), "this function is inlined at each call")
+                    .with_help("Functions with block or meta type parameters can't call themselves")
+                    .into(),
+            );
+        }
+        ctx.inline_depth.set(depth + 1);
+        let result = self.emit_inline_body(output, ctx, scope_stack, args, sizes);
+        ctx.inline_depth.set(depth);
+        result
+    }
+
+    fn emit_inline_body<Output: std::io::Write>(
+        &self,
+        output: &mut Output,
+        ctx: &ProgramContext,
+        scope_stack: &mut Vec<ScopeId>,
+        args: &[RawToken<'source>],
+        sizes: &[usize],
+    ) -> CheckResult<()> {
+        let bound = self
+            .signature
+            .params
+            .iter()
+            .zip(args)
+            .filter_map(|(param, arg)| match param {
+                Param::ParamExpr(
+                    param @ ParamExpr {
+                        name: Some(name), ..
+                    },
+                ) if param.is_comptime() => Some((*name, arg)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        stack_pop_remaining_parameters(output, &self.signature, sizes)?;
+        // Names in the body still refer to the scope of the definition
+        ListContent {
+            elements: inline_args(&self.body.elements, &bound),
+            scope:    self.body.scope,
+        }
+        .check_and_emit(output, ctx, scope_stack)
+    }
+}
+
 impl<'source> Parsable<'source> for FnDef<'source> {
+    /// Should be called when on the `fn` keyword
     fn parse(parser: &mut Parser<'source>) -> Result<FnDef<'source>, ParsingError<'source>> {
+        let start = parser.current_span.start;
+        parser.advance(); // Skip 'fn'
         let params = ParamList::parse(parser)?;
         let body = ListContent::parse(parser)?;
         Ok(FnDef {
             signature: FnSignature { params },
             body,
+            docs: vec![],
+            span: parser.span_from(start),
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::compile_to_string;
+
+    #[test]
+    fn recursive_inline_calls() {
+        let source = "loop = fn [b: Block] { b, loop {b}, }, loop {},";
+        let errors = compile_to_string(source).unwrap_err();
+        assert_eq!(errors[0].code, Some("recursive-inline"));
+
+        // Nesting an inlined call in itself is fine
+        let source = "g = fn {}, twice = fn [b: Block] { b, b, }, twice { twice {g}, },";
+        let output = compile_to_string(source).unwrap();
+        assert_eq!(output.matches("call g").count(), 4);
+    }
+}
@@ -1,3 +1,5 @@
+use std::cmp::Ordering;
+
 use super::*;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -5,7 +7,31 @@ pub struct FnSignature<'source> {
     pub params: ParamList<'source>,
 }
 
-impl FnSignature<'_> {
+impl<'source> FnSignature<'source> {
+    /// The parameters that are passed on the stack and the registers they are
+    /// popped into
+    pub fn param_locations(&self) -> impl Iterator<Item = (&ParamExpr<'source>, Register)> {
+        self.params
+            .iter()
+            .filter_map(|param| match param {
+                // Literals are only matched, blocks and tokens are inlined
+                Param::ParamExpr(param) if !param.is_comptime() => Some(param),
+                _ => None,
+            })
+            .enumerate()
+            .map(|(i, param)| (param, Register(i as u8)))
+    }
+
+    /// Functions with block or meta type parameters are inlined at every call,
+    /// as are those with parameters sized per call. Only the call knows the
+    /// size those are pushed with
+    pub fn is_inlined(&self, ctx: &ProgramContext) -> bool {
+        self.params.iter().any(|param| {
+            matches!(param, Param::ParamExpr(param)
+                if param.is_comptime() || param.is_sized_per_call(ctx))
+        })
+    }
+
     pub fn matches_args(&self, ctx: &ProgramContext, args: &Vec<RawToken>) -> bool {
         self.params.len() == args.len()
             && self
@@ -14,4 +40,103 @@ impl FnSignature<'_> {
                 .zip(args.iter())
                 .all(|(param, arg)| param.matches_arg(ctx, arg))
     }
+
+    /// Whether every call that matches `other` also matches this signature
+    pub fn covers(&self, other: &FnSignature, ctx: &ProgramContext) -> bool {
+        self.params.len() == other.params.len()
+            && self
+                .params
+                .iter()
+                .zip(&other.params)
+                .all(|(param, other_param)| param.covers(ctx, other_param))
+    }
+
+    /// Whether every parameter is at least as specific as the one of `other`
+    /// and one is more specific. Both should have the same number of params
+    pub fn is_more_specific_than(&self, other: &FnSignature, ctx: &ProgramContext) -> bool {
+        let mut more_specific = false;
+        for (param, other_param) in self.params.iter().zip(&other.params) {
+            match param.specificity(ctx).cmp(&other_param.specificity(ctx)) {
+                Ordering::Less => return false,
+                Ordering::Greater => more_specific = true,
+                Ordering::Equal => {}
+            }
+        }
+        more_specific
+    }
+
+    /// Why `args` don't match the signature, one reason per argument that
+    /// doesn't match. Empty if they match
+    pub fn mismatches(&self, ctx: &ProgramContext, args: &[RawToken]) -> Vec<String> {
+        if self.params.len() != args.len() {
+            let plural = if self.params.len() == 1 { "" } else { "s" };
+            return vec![format!(
+                "takes {} argument{plural}, found {}",
+                self.params.len(),
+                args.len()
+            )];
+        }
+        self.params
+            .iter()
+            .zip(args)
+            .enumerate()
+            .filter_map(|(i, (param, arg))| {
+                param
+                    .mismatch(ctx, arg)
+                    .map(|reason| format!("argument {}: {reason}", i + 1))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{compile_to_string, emitted_after};
+
+    #[test]
+    fn explain_mismatches() {
+        let notes = |source| {
+            let errors = compile_to_string(source).unwrap_err();
+            assert_eq!(errors[0].code, Some("signature-mismatch"));
+            errors[0].notes.clone()
+        };
+        assert_eq!(notes("T = type 2,\nf = fn 3d [a: T] {},\nf 2d 4u8,\n"), [
+            concat!(
+                "`f = fn 3d [a: T]` doesn't match:\n",
+                "argument 1: expected `3d`, found `2d`\n",
+                "argument 2: `4u8` is 1b, which doesn't overlap 2b of T",
+            )
+        ]);
+        assert_eq!(notes("T = type 2,\nf = fn 3d [a: T] {},\nf 3d,\n"), [
+            "`f = fn 3d [a: T]` doesn't match:\ntakes 2 arguments, found 1"
+        ]);
+    }
+
+    #[test]
+    fn rank_overloads() {
+        let source = concat!(
+            "W = type 1..=8,\nT = type 2,\n",
+            "f = fn 3d [a: W] {},\nf = fn 3d [a: T] {},\n",
+            "f 3d 5,\n",
+        );
+        // The more specific overload is called, it takes 2 bytes
+        let output = compile_to_string(source).unwrap();
+        assert_eq!(emitted_after(&output, "| Function call: f"), [
+            "push 2b Untyped(5)",
+            "call f",
+        ]);
+
+        // A literal matcher against a typed param is just as specific
+        let source = concat!(
+            "W = type 1..=8,\nT = type 2,\n",
+            "f = fn 3 [a: W] {},\nf = fn [a: W] [b: T] {},\n",
+            "f 3 5,\n",
+        );
+        let errors = compile_to_string(source).unwrap_err();
+        assert_eq!(errors[0].code, Some("ambiguous-call"));
+        assert_eq!(
+            errors[0].primary.message,
+            "both match the arguments `3` `5`"
+        );
+    }
 }
@@ -0,0 +1,105 @@
+use super::*;
+
+/// The elements of a function body with the arguments passed for its block
+/// and meta type parameters in place of the parameters
+///
+/// Names in the blocks still refer to the scope the blocks are written in
+pub(super) fn inline_args<'source>(
+    elements: &[Expr<'source>],
+    bound: &[(&str, &RawToken<'source>)],
+) -> Vec<Expr<'source>> {
+    elements
+        .iter()
+        .map(|element| inline_expr(element, bound))
+        .collect()
+}
+
+fn find_arg<'a, 'source>(
+    bound: &[(&str, &'a RawToken<'source>)],
+    name: &str,
+) -> Option<&'a RawToken<'source>> {
+    bound
+        .iter()
+        .find(|(param_name, _)| *param_name == name)
+        .map(|(_, arg)| *arg)
+}
+
+fn inline_expr<'source>(
+    element: &Expr<'source>,
+    bound: &[(&str, &RawToken<'source>)],
+) -> Expr<'source> {
+    match element {
+        Expr::FnCall(FnCall { name, args, .. }) if args.is_empty() => {
+            match find_arg(bound, name) {
+                Some(RawToken::Expr(expr)) => expr.clone(),
+                // An identifier stands for whatever it names. Errors point at
+                // the argument
+                Some(RawToken::Ident(Ident(ident), span)) => Expr::FnCall(FnCall {
+                    name: ident,
+                    args: vec![],
+                    span: span.clone(),
+                }),
+                _ => element.clone(),
+            }
+        }
+        Expr::FnCall(fn_call) => {
+            let name = match find_arg(bound, fn_call.name) {
+                Some(RawToken::Ident(Ident(ident), _)) => ident,
+                _ => fn_call.name,
+            };
+            let args = fn_call
+                .args
+                .iter()
+                .map(|arg| match arg {
+                    RawToken::Ident(Ident(name), _) => find_arg(bound, name).unwrap_or(arg).clone(),
+                    RawToken::Expr(expr) => RawToken::Expr(inline_expr(expr, bound)),
+                    _ => arg.clone(),
+                })
+                .collect();
+            Expr::FnCall(FnCall {
+                name,
+                args,
+                span: fn_call.span.clone(),
+            })
+        }
+        Expr::Move(move_) => {
+            let destination = match &move_.destination {
+                Destination::Alias(name, _) => match find_arg(bound, name) {
+                    Some(RawToken::Ident(Ident(ident), span)) => {
+                        Destination::Alias(ident, span.clone())
+                    }
+                    Some(RawToken::Expr(Expr::Register(register, span))) => {
+                        Destination::Register(*register, span.clone())
+                    }
+                    _ => move_.destination.clone(),
+                },
+                Destination::Register(..) => move_.destination.clone(),
+            };
+            Expr::Move(Move {
+                source: inline_expr(&move_.source, bound).into(),
+                destination,
+                span: move_.span.clone(),
+            })
+        }
+        Expr::FormatString(format_string) => Expr::FormatString(FormatString {
+            segments: format_string
+                .segments
+                .iter()
+                .map(|segment| match segment {
+                    FormatSegment::Expr(expr) => FormatSegment::Expr(inline_expr(expr, bound)),
+                    FormatSegment::Text(..) => segment.clone(),
+                })
+                .collect(),
+            span:     format_string.span.clone(),
+        }),
+        Expr::List(list) => Expr::List(List {
+            elements: ListContent {
+                elements: inline_args(&list.elements.elements, bound),
+                scope:    list.elements.scope,
+            },
+            ..list.clone()
+        }),
+        // Nested functions have their own parameters
+        _ => element.clone(),
+    }
+}
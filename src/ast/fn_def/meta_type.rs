@@ -0,0 +1,94 @@
+use super::*;
+
+/// Built-in types that match kinds of tokens instead of values. Parameters of
+/// these types are bound to the token at compile time, like block parameters
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MetaType {
+    /// A name like `x`
+    Ident,
+    /// A string literal
+    Str,
+    /// An integer literal
+    Number,
+    /// A single symbol like `=`
+    Symbol,
+    /// Any token
+    Any,
+}
+
+impl std::fmt::Display for MetaType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MetaType::Ident => write!(f, "an identifier"),
+            MetaType::Str => write!(f, "a string literal"),
+            MetaType::Number => write!(f, "an integer literal"),
+            MetaType::Symbol => write!(f, "a symbol"),
+            MetaType::Any => write!(f, "a token"),
+        }
+    }
+}
+
+impl MetaType {
+    pub fn from_name(name: &str) -> Option<MetaType> {
+        match name {
+            "Ident" => Some(MetaType::Ident),
+            "Str" => Some(MetaType::Str),
+            "Number" => Some(MetaType::Number),
+            "Symbol" => Some(MetaType::Symbol),
+            "Any" => Some(MetaType::Any),
+            _ => None,
+        }
+    }
+
+    pub fn matches(self, arg: &RawToken) -> bool {
+        match arg {
+            RawToken::Ident(..) => matches!(self, MetaType::Ident | MetaType::Any),
+            RawToken::Symbol(..) => matches!(self, MetaType::Symbol | MetaType::Any),
+            RawToken::Expr(expr) => self.matches_expr(expr),
+        }
+    }
+
+    pub fn matches_expr(self, expr: &Expr) -> bool {
+        match self {
+            MetaType::Str => matches!(expr, Expr::Bytes(..)),
+            MetaType::Number => matches!(expr, Expr::SmallValue(..)),
+            MetaType::Any => true,
+            MetaType::Ident | MetaType::Symbol => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{compile_to_string, emitted_after};
+
+    #[test]
+    fn match_and_bind_tokens() {
+        let let_ = "let = fn ([name: Ident] = [value: Any]) { name <- value },\nx = 0u8,\n";
+        // The body moves into the name that was passed
+        let output = compile_to_string(&format!("{let_}let x = 5,\n")).unwrap();
+        assert_eq!(emitted_after(&output, "| Inlined call: let"), [
+            "move 1b 0x05 -> x"
+        ]);
+
+        // Names that don't exist are reported at the call
+        let source = format!("{let_}let y = 5,\n");
+        let errors = compile_to_string(&source).unwrap_err();
+        assert_eq!(errors[0].code, Some("unknown-alias"));
+        assert_eq!(&source[errors[0].primary.span.clone()], "y");
+
+        let notes = |call| {
+            let errors = compile_to_string(&format!("{let_}{call}")).unwrap_err();
+            assert_eq!(errors[0].code, Some("signature-mismatch"));
+            errors[0].notes.clone()
+        };
+        assert_eq!(notes("let 4 = 5,"), [concat!(
+            "`let = fn [name: Ident] = [value: Any]` doesn't match:\n",
+            "argument 1: expected an identifier, found `4`",
+        )]);
+        assert_eq!(notes("let x is \"five\","), [concat!(
+            "`let = fn [name: Ident] = [value: Any]` doesn't match:\n",
+            "argument 2: expected `=`, found `is`",
+        )]);
+    }
+}
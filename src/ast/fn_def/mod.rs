@@ -1,3 +1,4 @@
+#[allow(clippy::module_inception)]
 mod fn_def;
 pub use fn_def::FnDef;
 
@@ -5,7 +6,10 @@ mod fn_signature;
 pub use fn_signature::FnSignature;
 
 mod params;
-pub use params::Param;
+pub use params::{source_text, Param, Specificity};
+
+mod meta_type;
+pub use meta_type::MetaType;
 
 mod param_expr;
 pub use param_expr::ParamExpr;
@@ -13,7 +17,16 @@ pub use param_expr::ParamExpr;
 mod param_list;
 pub use param_list::ParamList;
 
+mod overloads;
+pub use overloads::check_overloads;
+
+mod inline_args;
+use inline_args::inline_args;
+
+mod where_clause;
+use where_clause::parse_where_clause;
+
 mod stack_pop_remaining_parameters;
-pub(self) use stack_pop_remaining_parameters::stack_pop_remaining_parameters;
+use stack_pop_remaining_parameters::{passing_sizes, stack_pop_remaining_parameters};
 
-pub(self) use super::*;
+use super::*;
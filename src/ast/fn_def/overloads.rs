@@ -0,0 +1,125 @@
+use super::*;
+
+/// Compare the overload `fn_def` of `name` with the ones defined before it in
+/// `scope`. Overloads with the same signature are errors, overloads that can
+/// never be called are warnings
+pub fn check_overloads(ctx: &mut ProgramContext, name: &str, fn_def: &FnDef, scope: ScopeId) {
+    let earlier_overloads = ctx
+        .symbols
+        .get(name)
+        .into_iter()
+        .flatten()
+        .filter(|symbol| symbol.scope == scope)
+        .filter_map(|symbol| match symbol.value.as_ref() {
+            Expr::FnDef(fn_def) => Some(fn_def),
+            _ => None,
+        });
+    let mut diagnostics = vec![];
+    // Only the first problem with the new overload is reported
+    let mut reported = false;
+    for earlier in earlier_overloads {
+        let (signature, earlier_signature) = (&fn_def.signature, &earlier.signature);
+        let unreachable = earlier_signature.covers(signature, ctx)
+            && !signature.is_more_specific_than(earlier_signature, ctx);
+        if signature == earlier_signature || unreachable {
+            if reported {
+                continue;
+            }
+            reported = true;
+        }
+        if signature == earlier_signature {
+            diagnostics.push(
+                Diagnostic::error(
+                    fn_def.span.clone(),
+                    format!("{name} is already defined with this signature"),
+                )
+                .with_code("duplicate-overload")
+                .with_primary_label("defined again here")
+                .with_label(earlier.span.clone(), "first defined here")
+                .with_note("Parameter names don't distinguish overloads"),
+            );
+        } else if unreachable {
+            diagnostics.push(
+                Diagnostic::warning(
+                    fn_def.span.clone(),
+                    format!("This overload of {name} can never be called"),
+                )
+                .with_code("unreachable-overload")
+                .with_primary_label("never called")
+                .with_label(
+                    earlier.span.clone(),
+                    "every call to it matches this overload, which is at least as specific",
+                ),
+            );
+        } else if signature.covers(earlier_signature, ctx)
+            && !earlier_signature.is_more_specific_than(signature, ctx)
+        {
+            diagnostics.push(
+                Diagnostic::warning(
+                    earlier.span.clone(),
+                    format!("This overload of {name} can never be called"),
+                )
+                .with_code("shadowed-overload")
+                .with_primary_label("never called")
+                .with_label(
+                    fn_def.span.clone(),
+                    "every call to it matches this later overload, which is at least as specific",
+                ),
+            );
+        }
+    }
+    ctx.diagnostics.extend(diagnostics);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{errors::Severity, parser::compile_to_string};
+
+    #[test]
+    fn report_duplicate_and_unreachable_overloads() {
+        let source = concat!(
+            "Degrees = type 2,\n",
+            "Word = type 2,\n",
+            "turn = fn 2d [:Degrees] {},\n",
+            "turn = fn 2d [deg: Degrees] {},\n",
+            "turn = fn 2d [:Word] {},\n",
+            "turn = fn [a] [b] {},\n",
+        );
+        let diagnostics = compile_to_string(source)
+            .unwrap_err()
+            .iter()
+            .map(|diagnostic| (diagnostic.severity, diagnostic.code.unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(diagnostics, vec![
+            (Severity::Error, "duplicate-overload"),
+            (Severity::Warning, "unreachable-overload"),
+        ]);
+    }
+
+    #[test]
+    fn report_shadowed_overloads() {
+        // The duplicate `beep` is an error, so the warnings are returned
+        let source = concat!(
+            "Small = type 1..=2,\n",
+            "Big = type 1..=4,\n",
+            "send = fn [:Small] {},\n",
+            "send = fn [:Big] {},\n",
+            "beep = fn {},\n",
+            "beep = fn {},\n",
+        );
+        let diagnostics = compile_to_string(source).unwrap_err();
+        let codes = diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.severity, diagnostic.code.unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(codes, vec![
+            (Severity::Warning, "shadowed-overload"),
+            (Severity::Error, "duplicate-overload"),
+        ]);
+        // The earlier overload is the one that is never called
+        assert_eq!(
+            &source[diagnostics[0].primary.span.clone()],
+            "fn [:Small] {}"
+        );
+    }
+}
@@ -2,80 +2,343 @@ use super::*;
 
 #[derive(Debug, Clone)]
 pub struct ParamExpr<'source> {
-    pub name:     Option<&'source str>,
-    pub typename: Option<&'source str>,
+    pub name:      Option<&'source str>,
+    /// Types the argument has to have, from the brackets and the `where`
+    /// clause. All of them have to match
+    pub typenames: Vec<&'source str>,
+    pub span:      Span,
 }
 
 impl PartialEq for ParamExpr<'_> {
     fn eq(&self, other: &Self) -> bool {
         // Ignore param name
-        self.typename == other.typename
+        self.typenames == other.typenames
     }
 }
 
+/// Type of parameters that take a `{ ... }` block, which is inlined where the
+/// parameter is used
+pub const BLOCK_TYPE: &str = "Block";
+
 impl<'source> CompTimeSize<'source> for ParamExpr<'source> {
     fn number_bytes(&self, ctx: &ProgramContext) -> ByteSize {
-        let Some(typename) = self.typename else {
+        if self.is_comptime() {
             return ByteSize::AnySize;
-        };
-        if let Some(type_) = find_type(ctx, typename) {
-            type_.size.clone()
-        } else {
-            unreachable!("Type should exist at this point")
         }
+        let Some(size) = self.allowed_size(ctx) else {
+            unreachable!("Types should exist and overlap at this point")
+        };
+        size
     }
 }
 
-impl ParamExpr<'_> {
-    pub fn matches_arg(&self, ctx: &ProgramContext<'_>, arg: &Expr<'_>) -> bool {
-        match self.typename {
-            Some(typename) => match find_type(ctx, typename) {
-                Some(typeexpr::Type { type_fn, size }) => {
-                    size.overlap(&arg.number_bytes(ctx)).is_some()
-                        && match type_fn {
-                            Some(func) => func(arg),
-                            None => true,
-                        }
+impl<'source> ParamExpr<'source> {
+    pub fn is_block(&self) -> bool {
+        self.typenames.contains(&BLOCK_TYPE)
+    }
+
+    pub fn meta_types(&self) -> impl Iterator<Item = MetaType> + '_ {
+        self.typenames
+            .iter()
+            .filter_map(|typename| MetaType::from_name(typename))
+    }
+
+    /// Whether the argument is bound at compile time instead of passed on the
+    /// stack, which is the case for blocks and meta types
+    pub fn is_comptime(&self) -> bool {
+        self.is_block() || self.meta_types().next().is_some()
+    }
+
+    /// The types that are defined in the program rather than built in
+    fn defined_typenames(&self) -> impl Iterator<Item = &&'source str> {
+        self.typenames
+            .iter()
+            .filter(|typename| **typename != BLOCK_TYPE && MetaType::from_name(typename).is_none())
+    }
+
+    /// The sizes all of the types allow, `None` if a type doesn't exist or
+    /// the sizes don't overlap
+    pub fn allowed_size(&self, ctx: &ProgramContext) -> Option<ByteSize> {
+        self.defined_typenames()
+            .try_fold(ByteSize::AnySize, |size, typename| {
+                size.overlap(&find_type(ctx, typename)?.size)
+            })
+    }
+
+    /// Whether every argument that matches `other` also matches this
+    /// parameter. Predicates can't be compared, so a type with one is only
+    /// covered by the same type
+    pub fn covers(&self, ctx: &ProgramContext, other: &ParamExpr) -> bool {
+        if self.is_block() || other.is_block() {
+            return self.is_block() && other.is_block();
+        }
+        if self.meta_types().next().is_some() || other.meta_types().next().is_some() {
+            // Only compare the same types
+            return self.typenames.iter().all(|typename| {
+                MetaType::from_name(typename) == Some(MetaType::Any)
+                    || other.typenames.contains(typename)
+            });
+        }
+        let has_predicate = |typename: &&str| {
+            find_type(ctx, typename).is_some_and(|type_| type_.predicate.is_some())
+        };
+        if self
+            .typenames
+            .iter()
+            .any(|typename| has_predicate(typename) && !other.typenames.contains(typename))
+        {
+            return false;
+        }
+        match (self.allowed_size(ctx), other.allowed_size(ctx)) {
+            (Some(ByteSize::AnySize), Some(_)) => true,
+            (Some(size), Some(other_size)) => other_size.overlap(&size) == Some(other_size),
+            _ => false,
+        }
+    }
+
+    pub fn specificity(&self, ctx: &ProgramContext) -> Specificity {
+        if self.is_block() {
+            // Only matches blocks
+            return Specificity::ExactSize;
+        }
+        let size_specificity = match self.allowed_size(ctx) {
+            Some(ByteSize::Exact(_)) => Specificity::ExactSize,
+            Some(ByteSize::Range(_)) => Specificity::RangeSize,
+            _ => Specificity::Untyped,
+        };
+        // Meta types match a kind of token, like a type with many sizes
+        let meta_specificity = match self.meta_types().any(|meta| meta != MetaType::Any) {
+            true => Specificity::RangeSize,
+            false => Specificity::Untyped,
+        };
+        size_specificity.max(meta_specificity)
+    }
+
+    /// The first default size of the types that is allowed by all of them
+    pub fn default_size(&self, ctx: &ProgramContext) -> Option<usize> {
+        let size = self.allowed_size(ctx)?;
+        self.defined_typenames()
+            .filter_map(|typename| find_type(ctx, typename)?.default)
+            .find(|default| size.overlap(&ByteSize::Exact(*default)).is_some())
+    }
+
+    /// The number of bytes all arguments are passed with: the exact size of
+    /// the types, else their default size. `None` if the size isn't known or
+    /// depends on the argument
+    pub fn passing_size(&self, ctx: &ProgramContext) -> Option<usize> {
+        match self.allowed_size(ctx)? {
+            ByteSize::Exact(size) => Some(size),
+            ByteSize::Range(_) => self.default_size(ctx),
+            ByteSize::AnySize => None,
+        }
+    }
+
+    /// Whether the types allow a range of sizes without a default, so each
+    /// argument is passed with the smallest size that fits it
+    pub fn is_sized_per_call(&self, ctx: &ProgramContext) -> bool {
+        matches!(self.allowed_size(ctx), Some(ByteSize::Range(_)))
+            && self.passing_size(ctx).is_none()
+    }
+
+    /// Report types that don't exist and types whose sizes don't overlap
+    pub fn check_types(&self, ctx: &ProgramContext) -> CheckResult<()> {
+        if self.is_block() {
+            return Ok(());
+        }
+        if let Some(typename) = self
+            .defined_typenames()
+            .find(|typename| find_type(ctx, typename).is_none())
+        {
+            return compile_error(
+                self.span.clone(),
+                "unknown-type",
+                format!("Unknown type: {typename}"),
+            );
+        }
+        let metas = self
+            .meta_types()
+            .filter(|meta| *meta != MetaType::Any)
+            .collect::<Vec<_>>();
+        // Identifiers and symbols aren't values, which the defined types are for
+        let conflicting_metas = metas.windows(2).any(|pair| pair[0] != pair[1])
+            || (metas.contains(&MetaType::Ident) || metas.contains(&MetaType::Symbol))
+                && self.defined_typenames().next().is_some();
+        if conflicting_metas || self.allowed_size(ctx).is_none() {
+            return Err(Diagnostic::error(
+                self.span.clone(),
+                "Conflicting parameter types".to_string(),
+            )
+            .with_code("conflicting-types")
+            .with_primary_label(format!(
+                "no value can have all of the types {}",
+                self.typenames.join(", ")
+            ))
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Why `arg` doesn't match the parameter, `None` if it does. Only
+    /// parameters of meta types match tokens that aren't values
+    pub fn mismatch(&self, ctx: &ProgramContext<'_>, arg: &RawToken<'_>) -> Option<String> {
+        if let RawToken::Expr(expr) = arg {
+            return self.mismatch_value(ctx, expr);
+        }
+        let arg_text = source_text(ctx, arg.span());
+        if self.is_block() {
+            return Some(format!("expected a `{{ .. }}` block, found `{arg_text}`"));
+        }
+        if let Some(meta) = self.meta_types().find(|meta| !meta.matches(arg)) {
+            return Some(format!("expected {meta}, found `{arg_text}`"));
+        }
+        if self.meta_types().next().is_none() || self.defined_typenames().next().is_some() {
+            return Some(format!("expected a value, found `{arg_text}`"));
+        }
+        None
+    }
+
+    /// Why the value `arg` doesn't match the parameter, `None` if it does
+    pub fn mismatch_value(&self, ctx: &ProgramContext<'_>, arg: &Expr<'_>) -> Option<String> {
+        let arg_text = source_text(ctx, arg.span());
+        if self.is_block() {
+            return match arg {
+                Expr::List(List { mutable: false, .. }) => None,
+                _ => Some(format!("expected a `{{ .. }}` block, found `{arg_text}`")),
+            };
+        }
+        if let Some(meta) = self.meta_types().find(|meta| !meta.matches_expr(arg)) {
+            return Some(format!("expected {meta}, found `{arg_text}`"));
+        }
+        if !arg.is_value() {
+            return Some(format!("expected a value, found `{arg_text}`"));
+        }
+        // No types match anything. TODO: Type inference
+        // Unknown or conflicting types are reported at the definition
+        let Some(size) = self.allowed_size(ctx) else {
+            return Some(format!(
+                "the types {} don't exist or don't overlap",
+                self.typenames.join(", ")
+            ));
+        };
+        let arg_size = arg.number_bytes(ctx);
+        if size.overlap(&arg_size).is_none() {
+            return Some(format!(
+                "`{arg_text}` is {arg_size}, which doesn't overlap {size} of {}",
+                self.typenames.join(" + ")
+            ));
+        }
+        // Run the predicates of the types
+        for typename in self.defined_typenames() {
+            let Some(predicate) =
+                find_type(ctx, typename).and_then(|type_| type_.predicate.as_ref())
+            else {
+                continue;
+            };
+            match predicate.evaluate(ctx, arg) {
+                Ok(true) => {}
+                Ok(false) => return Some(format!("`{arg_text}` is not a {typename}")),
+                Err(ComptimeError::Raised(message)) => {
+                    return Some(format!("`{arg_text}` is not a {typename}: {message}"))
+                }
+                Err(ComptimeError::Invalid(message)) => {
+                    return Some(format!("the check of {typename} failed: {message}"))
                 }
-                None => unreachable!("Type should exist at this point"),
-            },
-            None => true, // I guess? TODO: Type inference
+            }
         }
+        None
     }
 }
 
 impl<'source> Parsable<'source> for ParamExpr<'source> {
+    /// Should be called when on the opening '['
     fn parse(parser: &mut Parser<'source>) -> Result<Self, ParsingError<'source>> {
-        let name = match parser.current_token.as_ref() {
-            Some(Ok(token)) => match token {
-                Token::Symbol(":") | Token::Symbol("]") => None,
-                Token::Ident => {
-                    let param_name = Some(parser.current_slice);
-                    parser.advance();
-                    param_name
-                }
-                _ => todo!("Handle error"),
-            },
-            _ => todo!("Handle error"),
+        let start = parser.current_span.start;
+        parser.advance(); // Skip '['
+        let name = match expect_param_token(parser)? {
+            Token::Symbol(":") | Token::Symbol("]") => None,
+            Token::Ident => {
+                let param_name = Some(parser.current_slice);
+                parser.advance();
+                param_name
+            }
+            _ => {
+                return Err(ParsingError::InvalidParam(
+                    "expected a parameter name, `:` or `]`",
+                    parser.lexer.extras.clone(),
+                    parser.current_span.clone(),
+                ))
+            }
         };
 
-        let typename = match parser.current_token.as_ref() {
-            Some(Ok(Token::Symbol("]"))) => None,
-            Some(Ok(Token::Symbol(":"))) => {
+        let typenames = match expect_param_token(parser)? {
+            Token::Symbol("]") => vec![],
+            // `[name: Type + OtherType]`
+            Token::Symbol(":") => {
                 parser.advance(); // Skip ':'
-                match parser.current_token.as_ref() {
-                    Some(Ok(Token::Ident)) => {
-                        let typename = Some(parser.current_slice);
-                        parser.advance();
-                        typename
+                let mut typenames = vec![];
+                loop {
+                    match expect_param_token(parser)? {
+                        Token::Ident => {
+                            typenames.push(parser.current_slice);
+                            parser.advance();
+                        }
+                        _ => {
+                            return Err(ParsingError::InvalidParam(
+                                "expected the name of a type",
+                                parser.lexer.extras.clone(),
+                                parser.current_span.clone(),
+                            ))
+                        }
+                    }
+                    match expect_param_token(parser)? {
+                        Token::Symbol("+") => parser.advance(),
+                        _ => break typenames,
                     }
-                    other => panic!("Don't think {other:?} is allowed here (typeexpr)"),
                 }
             }
-            _ => todo!(),
+            _ => {
+                return Err(ParsingError::InvalidParam(
+                    "expected `:` or `]` after the parameter name",
+                    parser.lexer.extras.clone(),
+                    parser.current_span.clone(),
+                ))
+            }
         };
 
-        parser.advance(); // Skip ']'
-        Ok(ParamExpr { name, typename })
+        match expect_param_token(parser)? {
+            Token::Symbol("]") => parser.advance(),
+            _ => {
+                return Err(ParsingError::InvalidParam(
+                    "expected `]` to close the parameter",
+                    parser.lexer.extras.clone(),
+                    parser.current_span.clone(),
+                ))
+            }
+        }
+        Ok(ParamExpr {
+            name,
+            typenames,
+            span: parser.span_from(start),
+        })
+    }
+}
+
+/// The current token, or an error at the end of the file or on invalid tokens
+fn expect_param_token<'source>(
+    parser: &Parser<'source>,
+) -> Result<Token<'source>, ParsingError<'source>> {
+    match parser.current_token.as_ref() {
+        Some(Ok(token)) => Ok(token.clone()),
+        Some(Err(err)) => Err(ParsingError::TokenError(
+            err.clone(),
+            parser.lexer.extras.clone(),
+            parser.current_span.clone(),
+        )),
+        None => Err(ParsingError::AbruptEof(
+            "parameter",
+            parser.lexer.extras.clone(),
+            parser.current_span.clone(),
+            vec![Token::Ident, Token::Symbol(":"), Token::Symbol("]")],
+        )),
     }
 }
@@ -5,28 +5,72 @@ pub type ParamList<'source> = Vec<Param<'source>>;
 impl<'source> Parsable<'source> for ParamList<'source> {
     fn parse(parser: &mut Parser<'source>) -> Result<ParamList<'source>, ParsingError<'source>> {
         let mut params = Vec::new();
+        // Inside `( .. )`, which groups the parameters like in `fn (a [b: T]) {}`
+        let mut in_group = false;
         while let Some(Ok(token)) = parser.current_token.as_ref() {
             match token {
+                Token::Symbol("(") if !in_group => {
+                    in_group = true;
+                    parser.advance();
+                }
+                Token::Symbol(")") if in_group => {
+                    in_group = false;
+                    parser.advance();
+                }
+                Token::Symbol("{" | "(") | Token::KeywordWhere if in_group => {
+                    return Err(ParsingError::unexpected_token(
+                        "params",
+                        parser.lexer.extras.clone(),
+                        parser.current_span.clone(),
+                        token.clone(),
+                        vec![Token::Symbol(")")],
+                    ))
+                }
                 Token::Symbol("{") => {
                     parser.advance();
                     break;
                 }
                 Token::Symbol(",") => {
-                    return Err(ParsingError::UnexpectedToken(
+                    return Err(ParsingError::unexpected_token(
                         "params",
                         parser.lexer.extras.clone(),
+                        parser.current_span.clone(),
                         token.clone(),
                         vec![Token::Symbol("{")],
                     ))
                 }
-                Token::Symbol("[") => {
-                    parser.advance(); // Skip '['
-                    params.push(Param::ParamExpr(ParamExpr::parse(parser)?))
+                Token::KeywordWhere => parse_where_clause(parser, &mut params)?,
+                Token::Symbol("[") => match ParamExpr::parse(parser) {
+                    Ok(param) => params.push(Param::ParamExpr(param)),
+                    Err(error) => {
+                        // Continue after the broken parameter
+                        parser.errors.push(error);
+                        loop {
+                            match parser.current_token {
+                                Some(Ok(Token::Symbol("]"))) => {
+                                    parser.advance();
+                                    break;
+                                }
+                                Some(Ok(Token::Symbol("{" | ","))) | None => break,
+                                _ => parser.advance(),
+                            }
+                        }
+                    }
+                },
+                Token::FormatString(_) | Token::LeftArrow | Token::RightArrow => {
+                    return Err(ParsingError::unexpected_token(
+                        "params",
+                        parser.lexer.extras.clone(),
+                        parser.current_span.clone(),
+                        token.clone(),
+                        vec![Token::Symbol("["), Token::Symbol("{")],
+                    ))
                 }
                 token => {
                     params.push(Param::LiteralMatcher(RawToken::from_token(
                         token,
-                        parser.lexer.slice(),
+                        parser.current_slice,
+                        parser.current_span.clone(),
                     )));
                     parser.advance();
                 }
@@ -35,3 +79,32 @@ impl<'source> Parsable<'source> for ParamList<'source> {
         Ok(params)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{compile_to_string, emitted_after};
+
+    #[test]
+    fn grouped_params_and_string_literals() {
+        let definitions = concat!(
+            "T = type 2,\n",
+            "f = fn (\"str lit\" ident [a: T]) {},\n",
+            "g = fn \"str lit\" ident [a: T] {},\n",
+        );
+        for name in ["f", "g"] {
+            let source = format!("{definitions}{name} \"str lit\" ident 3,\n");
+            let output = compile_to_string(&source).unwrap();
+            assert_eq!(
+                emitted_after(&output, &format!("| Function call: {name}")),
+                ["push 2b Untyped(3)".to_string(), format!("call {name}")]
+            );
+
+            // Grouped or not, the parameters are the same
+            let source = format!("{definitions}{name} ident,\n");
+            let errors = compile_to_string(&source).unwrap_err();
+            assert_eq!(errors[0].notes, [format!(
+                "`{name} = fn \"str lit\" ident [a: T]` doesn't match:\ntakes 3 arguments, found 1"
+            )]);
+        }
+    }
+}
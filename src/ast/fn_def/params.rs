@@ -8,19 +8,61 @@ pub enum Param<'source> {
 
 impl Param<'_> {
     pub fn matches_arg(&self, ctx: &ProgramContext<'_>, arg: &RawToken<'_>) -> bool {
+        self.mismatch(ctx, arg).is_none()
+    }
+
+    /// Why `arg` doesn't match the parameter, `None` if it does
+    pub fn mismatch(&self, ctx: &ProgramContext<'_>, arg: &RawToken<'_>) -> Option<String> {
         match self {
-            Param::LiteralMatcher(raw_token) => arg == raw_token,
-            Param::ParamExpr(param_expr) => {
-                if let RawToken::Expr(expr) = arg {
-                    param_expr.matches_arg(ctx, expr)
-                } else {
-                    false
-                }
+            Param::LiteralMatcher(raw_token) => (arg != raw_token).then(|| {
+                format!(
+                    "expected `{}`, found `{}`",
+                    source_text(ctx, raw_token.span()),
+                    source_text(ctx, arg.span())
+                )
+            }),
+            Param::ParamExpr(param_expr) => param_expr.mismatch(ctx, arg),
+        }
+    }
+}
+
+impl Param<'_> {
+    /// Whether every argument that matches `other` also matches this parameter
+    pub fn covers(&self, ctx: &ProgramContext<'_>, other: &Param<'_>) -> bool {
+        match (self, other) {
+            (_, Param::LiteralMatcher(raw_token)) => self.matches_arg(ctx, raw_token),
+            (Param::LiteralMatcher(_), Param::ParamExpr(_)) => false,
+            (Param::ParamExpr(param_expr), Param::ParamExpr(other)) => {
+                param_expr.covers(ctx, other)
             }
         }
     }
 }
 
+/// How specific a parameter is. When several overloads match a call, the one
+/// with the more specific parameters is called
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Specificity {
+    Untyped,
+    RangeSize,
+    ExactSize,
+    Literal,
+}
+
+impl Param<'_> {
+    pub fn specificity(&self, ctx: &ProgramContext<'_>) -> Specificity {
+        match self {
+            Param::LiteralMatcher(_) => Specificity::Literal,
+            Param::ParamExpr(param_expr) => param_expr.specificity(ctx),
+        }
+    }
+}
+
+/// The source code at `span`
+pub fn source_text<'a>(ctx: &'a ProgramContext, span: Span) -> &'a str {
+    ctx.file_context.source.get(span).unwrap_or("?")
+}
+
 impl<'source> CompTimeSize<'source> for Param<'source> {
     fn number_bytes(&self, ctx: &'source ProgramContext) -> ByteSize {
         match &self {
@@ -1,35 +1,68 @@
 use super::*;
 
+/// Pop each parameter with the size it was pushed with
 pub(super) fn stack_pop_remaining_parameters<Output: std::io::Write>(
-    ctx: &ProgramContext,
     output: &mut Output,
-    params: &Vec<Param>,
+    signature: &FnSignature,
+    sizes: &[usize],
 ) -> CheckResult<()> {
-    for (i, param) in params
-        .iter()
-        .filter_map(|p| match p {
-            // Ignore literals
-            Param::ParamExpr(p) => Some(p),
-            Param::LiteralMatcher(_) => None,
-        })
-        .enumerate()
-    {
-        match param.number_bytes(ctx) {
-            // todo
-            _ => {
-                write!(
-                    output,
-                    "{pad}pop {size} -> {reg}       | {i}th argument",
-                    pad = current_padding(),
-                    size = param.number_bytes(ctx),
-                    reg = free_register()
-                )?;
-                if let Some(name) = param.name {
-                    write!(output, ": {name}")?;
-                }
-                writeln!(output)?;
-            }
+    for (i, ((param, location), size)) in signature.param_locations().zip(sizes).enumerate() {
+        write!(
+            output,
+            "{pad}pop {size}b -> {location}       | {i}th argument",
+            pad = current_padding(),
+        )?;
+        if let Some(name) = param.name {
+            write!(output, ": {name}")?;
         }
+        writeln!(output)?;
     }
     Ok(())
 }
+
+/// The sizes all calls push the parameters with, for functions that aren't
+/// inlined
+pub(super) fn passing_sizes(
+    ctx: &ProgramContext,
+    signature: &FnSignature,
+) -> CheckResult<Vec<usize>> {
+    signature
+        .param_locations()
+        .map(|(param, _)| {
+            param.passing_size(ctx).ok_or_else(|| {
+                Diagnostic::error(
+                    param.span.clone(),
+                    "Unknown size of the parameter".to_string(),
+                )
+                .with_code("unknown-size")
+                .with_primary_label("the size arguments are passed with isn't known")
+                .with_help("Give the parameter a type with a size, like `[name: u8]`")
+                .into()
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{compile_to_string, emitted_after};
+
+    #[test]
+    fn pop_what_is_pushed() {
+        let source = "u8 = type 1, D = type 1..=4 default 2, f = fn [a: u8] [b: D] {}, f 3 0x1234,";
+        let output = compile_to_string(source).unwrap();
+        assert_eq!(emitted_after(&output, "f:"), [
+            "pop 1b -> r0       | 0th argument: a",
+            "pop 2b -> r1       | 1th argument: b",
+            "ret",
+        ]);
+        assert_eq!(emitted_after(&output, "| Function call: f"), [
+            "push 2b Untyped(4660)",
+            "push 1b Untyped(3)",
+            "call f",
+        ]);
+
+        let errors = compile_to_string("f = fn [a] {},").unwrap_err();
+        assert_eq!(errors[0].code, Some("unknown-size"));
+    }
+}
@@ -0,0 +1,128 @@
+use super::*;
+
+/// Should be called when on the `where` keyword. Adds the types of
+/// `where name: Type + OtherType, ..` to the parameters with that name and
+/// stops at the `{` of the body
+pub(super) fn parse_where_clause<'source>(
+    parser: &mut Parser<'source>,
+    params: &mut ParamList<'source>,
+) -> Result<(), ParsingError<'source>> {
+    parser.advance(); // Skip 'where'
+    let mut constrained = vec![];
+    loop {
+        match expect_token(parser, vec![Token::Ident, Token::Symbol("{")])? {
+            Token::Symbol("{") => return Ok(()),
+            Token::Symbol(",") => parser.advance(),
+            Token::Ident => {
+                let name = parser.current_slice;
+                let name_span = parser.current_span.clone();
+                if constrained.contains(&name) {
+                    return Err(ParsingError::InvalidConstraint(
+                        "this parameter is already constrained, join the types with `+`",
+                        parser.lexer.extras.clone(),
+                        name_span,
+                    ));
+                }
+                constrained.push(name);
+                parser.advance();
+
+                if expect_token(parser, vec![Token::Symbol(":")])? != Token::Symbol(":") {
+                    return Err(unexpected_token(parser, vec![Token::Symbol(":")]));
+                }
+                parser.advance(); // Skip ':'
+                let mut typenames = vec![];
+                loop {
+                    if expect_token(parser, vec![Token::Ident])? != Token::Ident {
+                        return Err(unexpected_token(parser, vec![Token::Ident]));
+                    }
+                    typenames.push(parser.current_slice);
+                    parser.advance();
+                    match parser.current_token {
+                        Some(Ok(Token::Symbol("+"))) => parser.advance(),
+                        _ => break,
+                    }
+                }
+
+                let mut matching_params = params
+                    .iter_mut()
+                    .filter_map(|param| match param {
+                        Param::ParamExpr(param) if param.name == Some(name) => Some(param),
+                        _ => None,
+                    })
+                    .peekable();
+                if matching_params.peek().is_none() {
+                    return Err(ParsingError::InvalidConstraint(
+                        "there is no parameter with this name",
+                        parser.lexer.extras.clone(),
+                        name_span,
+                    ));
+                }
+                for param in matching_params {
+                    param.typenames.extend(&typenames);
+                }
+            }
+            _ => {
+                return Err(unexpected_token(parser, vec![
+                    Token::Ident,
+                    Token::Symbol("{"),
+                ]))
+            }
+        }
+    }
+}
+
+/// The current token, or an error at the end of the file or on invalid tokens
+fn expect_token<'source>(
+    parser: &Parser<'source>,
+    expected: Vec<Token<'source>>,
+) -> Result<Token<'source>, ParsingError<'source>> {
+    match parser.current_token.as_ref() {
+        Some(Ok(token)) => Ok(token.clone()),
+        Some(Err(err)) => Err(ParsingError::TokenError(
+            err.clone(),
+            parser.lexer.extras.clone(),
+            parser.current_span.clone(),
+        )),
+        None => Err(ParsingError::AbruptEof(
+            "where clause",
+            parser.lexer.extras.clone(),
+            parser.current_span.clone(),
+            expected,
+        )),
+    }
+}
+
+fn unexpected_token<'source>(
+    parser: &Parser<'source>,
+    expected: Vec<Token<'source>>,
+) -> ParsingError<'source> {
+    let Some(Ok(token)) = parser.current_token.clone() else {
+        unreachable!("Only called on valid tokens")
+    };
+    ParsingError::unexpected_token(
+        "where clause",
+        parser.lexer.extras.clone(),
+        parser.current_span.clone(),
+        token,
+        expected,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::compile_to_string;
+
+    #[test]
+    fn merge_constraints() {
+        let source = concat!(
+            "A = type 2, B = type 2, C = type 1..=2, D = type 1,\n",
+            "f = fn [a: A] [b] where a: B + C, b: D, {},\n",
+            "f 1,\n",
+        );
+        // The mismatch shows the signature with the merged types
+        let errors = compile_to_string(source).unwrap_err();
+        assert_eq!(errors[0].notes, [
+            "`f = fn [a: A + B + C] [b: D]` doesn't match:\ntakes 2 arguments, found 1"
+        ]);
+    }
+}
@@ -0,0 +1,155 @@
+use logos::Logos;
+
+use super::*;
+use crate::lexer::FormatChunk;
+
+/// `f"The answer is {answer}"`
+///
+/// Calls with a format string argument are expanded into one call per
+/// segment, so `print f"x = {x}"` becomes `print "x = "` and `print x`
+#[derive(Debug, Clone)]
+pub struct FormatString<'source> {
+    pub segments: Vec<FormatSegment<'source>>,
+    pub span:     Span,
+}
+
+#[derive(Debug, Clone)]
+pub enum FormatSegment<'source> {
+    Text(Box<[u8]>, Span),
+    Expr(Expr<'source>),
+}
+
+impl PartialEq for FormatString<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        // Ignore spans
+        self.segments.len() == other.segments.len()
+            && self
+                .segments
+                .iter()
+                .zip(&other.segments)
+                .all(|pair| match pair {
+                    (FormatSegment::Text(a, _), FormatSegment::Text(b, _)) => a == b,
+                    (FormatSegment::Expr(a), FormatSegment::Expr(b)) => a == b,
+                    _ => false,
+                })
+    }
+}
+
+impl<'source> FormatSegment<'source> {
+    /// The segment as the argument of a call
+    pub fn to_arg(&self) -> RawToken<'source> {
+        match self {
+            FormatSegment::Text(bytes, span) => {
+                RawToken::Expr(Expr::Bytes(bytes.clone(), span.clone()))
+            }
+            // `{name}` is resolved like `(name)`
+            FormatSegment::Expr(expr) => RawToken::Expr(expr.clone()),
+        }
+    }
+
+    /// Report names that aren't parameters, only those have a value to pass
+    pub fn check_names(&self, ctx: &ProgramContext, scope: ScopeId) -> CheckResult<()> {
+        let FormatSegment::Expr(Expr::FnCall(FnCall { name, args, span })) = self else {
+            return Ok(());
+        };
+        let symbol = ctx
+            .lookup(name, scope)
+            .last()
+            .map(|symbol| symbol.value.as_ref());
+        if !args.is_empty() || matches!(symbol, Some(Expr::Local(..))) {
+            return Ok(());
+        }
+        Err(Diagnostic::error(
+            span.clone(),
+            "Only parameters can be interpolated".to_string(),
+        )
+        .with_code("invalid-interpolation")
+        .with_primary_label(format!("`{name}` is not a parameter"))
+        .with_help("Pass the value as its own argument instead")
+        .into())
+    }
+}
+
+impl<'source> Parsable<'source> for FormatString<'source> {
+    /// Should be called when on the format string
+    fn parse(parser: &mut Parser<'source>) -> Result<Self, ParsingError<'source>> {
+        let Some(Ok(Token::FormatString(chunks))) = parser.current_token.clone() else {
+            unreachable!("FormatString::parse is only called on format strings")
+        };
+        let span = parser.current_span.clone();
+        let mut segments = vec![];
+        for chunk in chunks.iter() {
+            match chunk {
+                FormatChunk::Text(bytes, span) => {
+                    segments.push(FormatSegment::Text(bytes.clone(), span.clone()))
+                }
+                FormatChunk::Expr(span) => {
+                    segments.push(FormatSegment::Expr(parse_embedded(parser, span.clone())?))
+                }
+            }
+        }
+        parser.advance();
+        Ok(FormatString { segments, span })
+    }
+}
+
+/// Parse the expression at `span` with a separate parser that stops at the end
+/// of the span
+fn parse_embedded<'source>(
+    parser: &mut Parser<'source>,
+    span: Span,
+) -> Result<Expr<'source>, ParsingError<'source>> {
+    let source = &parser.lexer.source()[..span.end];
+    let mut lexer = Token::lexer_with_extras(source, parser.lexer.extras.clone());
+    lexer.bump(span.start);
+    let mut embedded = Parser::new(lexer);
+    embedded.advance();
+    let expr = Expr::parse(&mut embedded)?;
+    parser.errors.append(&mut embedded.errors);
+    match embedded.current_token {
+        None => Ok(expr),
+        Some(Ok(token)) => Err(ParsingError::unexpected_token(
+            "format string expression",
+            parser.lexer.extras.clone(),
+            embedded.current_span,
+            token,
+            vec![Token::Symbol("}")],
+        )),
+        Some(Err(err)) => Err(ParsingError::TokenError(
+            err,
+            parser.lexer.extras.clone(),
+            embedded.current_span,
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{compile_to_string, emitted_after};
+
+    #[test]
+    fn interpolate_parameters() {
+        let source = concat!(
+            "T = type 1,\n",
+            "print = fn [v: T] {},\n",
+            "print = fn [s: Str] {},\n",
+            "show = fn [v: T] { print f\"v = {v}\" },\n",
+        );
+        let output = compile_to_string(source).unwrap();
+        // The parameter is passed from where it is stored
+        assert_eq!(emitted_after(&output, "| Function call: print"), [
+            "push 1b r0",
+            "call print",
+            "ret",
+        ]);
+
+        let errors = compile_to_string("prnt f\"a {3} b\",").unwrap_err();
+        assert_eq!(errors[0].code, Some("unknown-function"));
+
+        // Aliases have no value to pass
+        let source = format!("{source}x = 3,\nprint f\"hello {{x}} world\",\n");
+        let errors = compile_to_string(&source).unwrap_err();
+        assert_eq!(errors[0].code, Some("invalid-interpolation"));
+        assert_eq!(&source[errors[0].primary.span.clone()], "x");
+    }
+}
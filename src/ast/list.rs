@@ -0,0 +1,151 @@
+use super::*;
+
+/// `{1, 2, 3}` is a const list, `[1, 2, 3]` a mutable one
+///
+/// Const lists are emitted as static data, mutable lists are stored on the
+/// stack. Lists that contain instructions rather than values are blocks
+#[derive(Debug, Clone)]
+pub struct List<'source> {
+    pub mutable:  bool,
+    pub elements: ListContent<'source>,
+    pub span:     Span,
+}
+
+impl PartialEq for List<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        // Ignore span
+        self.mutable == other.mutable && self.elements == other.elements
+    }
+}
+
+impl CompTimeSize<'_> for List<'_> {
+    fn number_bytes(&self, ctx: &ProgramContext) -> ByteSize {
+        // Blocks are code, they have no size as data
+        if !self.is_data() {
+            return ByteSize::AnySize;
+        }
+        self.elements
+            .elements
+            .iter()
+            .fold(ByteSize::Exact(0), |size, element| {
+                size + element.number_bytes(ctx)
+            })
+    }
+}
+
+impl<'source> List<'source> {
+    /// Whether all elements are values that can be written out as data
+    pub fn is_data(&self) -> bool {
+        self.elements.elements.iter().all(|element| match element {
+            Expr::SmallValue(..) | Expr::Bytes(..) => true,
+            Expr::List(list) => list.is_data(),
+            _ => false,
+        })
+    }
+
+    /// Emit the elements of a data list under the name `name`
+    pub fn emit_data<Output: std::io::Write>(
+        &self,
+        output: &mut Output,
+        ctx: &ProgramContext,
+        name: &str,
+    ) -> CheckResult<()> {
+        let mut values = vec![];
+        self.data_values(ctx, &mut values);
+        if self.mutable {
+            writeln!(output, "\n|| Mutable list {name}\n{name}: ")?;
+            // The first element ends up at the top of the stack
+            for (size, value) in values.iter().rev() {
+                writeln!(output, "{}    push {size}b {value}", current_padding())?;
+            }
+        } else {
+            writeln!(output, "\n|| Const list {name}\n{name}: ")?;
+            for (size, value) in &values {
+                writeln!(output, "{}    data {size}b {value}", current_padding())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The size and hexadecimal value of each element, nested lists flattened
+    fn data_values(&self, ctx: &ProgramContext, values: &mut Vec<(usize, String)>) {
+        for element in &self.elements.elements {
+            match element {
+                Expr::SmallValue(value, _) => {
+                    // Untyped values take as little space as they can
+                    let size = match value.number_bytes(ctx) {
+                        ByteSize::Exact(size) => size,
+                        _ => value.min_bytes(),
+                    };
+                    values.push((size, value.to_hex(size)));
+                }
+                Expr::Bytes(bytes, _) => values.push((bytes.len(), format!("{element:?}"))),
+                Expr::List(list) => list.data_values(ctx, values),
+                _ => unreachable!("Only called on data lists"),
+            }
+        }
+    }
+}
+
+impl<'source> AstNode<'source> for List<'source> {
+    fn build_context(&self, ctx: &mut ProgramContext<'source>, scope_stack: &mut Vec<ScopeId>) {
+        self.elements.build_context(ctx, scope_stack);
+    }
+
+    fn check_and_emit<Output: std::io::Write>(
+        &self,
+        output: &mut Output,
+        ctx: &ProgramContext<'source>,
+        scope_stack: &mut Vec<ScopeId>,
+    ) -> CheckResult<()> {
+        // A list on its own is a block
+        self.elements.check_and_emit(output, ctx, scope_stack)
+    }
+}
+
+impl<'source> Parsable<'source> for List<'source> {
+    /// Should be called when on the `{` or `[`
+    fn parse(parser: &mut Parser<'source>) -> Result<List<'source>, ParsingError<'source>> {
+        let start = parser.current_span.start;
+        let mutable = matches!(parser.current_token, Some(Ok(Token::Symbol("["))));
+        parser.advance(); // Skip '{' or '['
+        let elements = ListContent::parse(parser)?;
+        Ok(List {
+            mutable,
+            elements,
+            span: parser.span_from(start),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{compile_to_string, emitted_after};
+
+    #[test]
+    fn emit_lists_as_data() {
+        let output = compile_to_string("x = {1u8, {2, 3u16}},\ny = [4u8, 300],\n").unwrap();
+        assert_eq!(emitted_after(&output, "|| Const list x"), [
+            "x:",
+            "data 1b 0x01",
+            "data 1b 0x02",
+            "data 2b 0x0003",
+        ]);
+        assert_eq!(emitted_after(&output, "|| Mutable list y"), [
+            "y:",
+            "push 2b 0x012C",
+            "push 1b 0x04",
+        ]);
+    }
+
+    #[test]
+    fn blocks_are_no_values() {
+        // A block containing a call has no size and can't be passed as a value
+        let source = "T = type 2, g = fn {}, f = fn [a: T] {}, f {g},";
+        let errors = compile_to_string(source).unwrap_err();
+        assert_eq!(errors[0].code, Some("signature-mismatch"));
+
+        let errors = compile_to_string("x = {1, y = 2},").unwrap_err();
+        assert_eq!(errors[0].code, Some("mixed-list"));
+    }
+}
@@ -1,8 +1,18 @@
 use super::*;
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, Default)]
 pub struct ListContent<'source> {
     pub elements: Vec<Expr<'source>>,
+    /// Names defined in the list are in this scope. Copies of the list, like
+    /// inlined blocks, share the scope
+    pub scope:    ScopeId,
+}
+
+impl PartialEq for ListContent<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        // Ignore scope
+        self.elements == other.elements
+    }
 }
 
 impl std::fmt::Debug for ListContent<'_> {
@@ -13,11 +23,16 @@ impl std::fmt::Debug for ListContent<'_> {
 
 impl<'source> AstNode<'source> for ListContent<'source> {
     fn build_context(&self, ctx: &mut ProgramContext<'source>, scope_stack: &mut Vec<ScopeId>) {
-        scope_stack.push(next_scope());
+        if let Some(parent) = scope_stack.last() {
+            ctx.scope_parents.insert(self.scope, *parent);
+        }
+        scope_stack.push(self.scope);
 
         for element in self.elements.iter() {
             element.build_context(ctx, scope_stack);
         }
+
+        scope_stack.pop();
     }
 
     fn check_and_emit<Output: std::io::Write>(
@@ -26,8 +41,7 @@ impl<'source> AstNode<'source> for ListContent<'source> {
         ctx: &ProgramContext<'source>,
         scope_stack: &mut Vec<ScopeId>,
     ) -> CheckResult<()> {
-        // Start a new scope
-        scope_stack.push(next_scope());
+        scope_stack.push(self.scope);
 
         for element in &self.elements {
             element.check_and_emit(output, ctx, scope_stack)?;
@@ -41,50 +55,80 @@ impl<'source> AstNode<'source> for ListContent<'source> {
 impl<'source> Parsable<'source> for ListContent<'source> {
     fn parse(parser: &mut Parser<'source>) -> Result<ListContent<'source>, ParsingError<'source>> {
         let mut elements = vec![];
+        let scope = next_scope();
+        parser.list_depth += 1;
         loop {
             let token = match parser.current_token.as_ref() {
                 Some(Ok(token)) => token,
-                Some(Err(())) => {
-                    return Err(ParsingError::TokenError(format!(
-                        "Lexer error in {file}@{line}",
-                        file = parser.lexer.extras.filename,
-                        line = parser.lexer.extras.line,
-                    )))
+                Some(Err(err)) => {
+                    let error = ParsingError::TokenError(
+                        err.clone(),
+                        parser.lexer.extras.clone(),
+                        parser.current_span.clone(),
+                    );
+                    parser.recover(error);
+                    continue;
+                }
+                None => {
+                    // Docs at the end of the file don't document anything
+                    parser.take_docs();
+                    break;
                 }
-                None => break,
             };
             match token {
                 Token::Symbol(",") => parser.advance(),
-                Token::Symbol("]") | Token::Symbol("}") => {
+                Token::Symbol("]") | Token::Symbol("}") if parser.list_depth > 1 => {
+                    // Docs at the end of a list don't document anything
+                    parser.take_docs();
                     parser.advance();
                     break;
                 }
 
                 Token::Ident
                 | Token::Int(_)
-                | Token::String
+                | Token::Reg(_)
+                | Token::String(_)
+                | Token::FormatString(_)
+                | Token::Symbol("{" | "[")
                 | Token::KeywordFn
-                | Token::KeywordType => elements.push(Expr::parse(parser)?),
+                | Token::KeywordType => {
+                    let docs = parser.take_docs();
+                    let mut element = match Expr::parse(parser) {
+                        Ok(element) => element,
+                        Err(error) => {
+                            parser.recover(error);
+                            continue;
+                        }
+                    };
+                    match element {
+                        Expr::Assign(ref mut assign) => assign.docs = docs,
+                        Expr::FnDef(ref mut fn_def) => fn_def.docs = docs,
+                        _ => {}
+                    }
+                    elements.push(element)
+                }
 
-                Token::Symbol(_) => {
-                    return Err(ParsingError::UnexpectedToken(
+                _ => {
+                    let error = ParsingError::unexpected_token(
                         "listcontent",
                         parser.lexer.extras.clone(),
+                        parser.current_span.clone(),
                         token.clone(),
                         vec![
                             Token::Symbol(","),
                             Token::Symbol("]"),
                             Token::Symbol("}"),
                             Token::Ident,
-                            Token::Int(0),
-                            Token::String,
+                            Token::Int(SmallValue::Untyped(0)),
+                            Token::String(Box::default()),
                             Token::KeywordFn,
                         ],
-                    ))
+                    );
+                    parser.recover(error);
                 }
-                other => panic!("Impossible: {other:?}"),
             }
         }
-        Ok(ListContent { elements })
+        parser.list_depth -= 1;
+        Ok(ListContent { elements, scope })
     }
 }
@@ -0,0 +1,15 @@
+use super::*;
+
+/// A parameter of a function, as a name in the body of the function
+#[derive(Debug, Clone, PartialEq)]
+pub struct Local<'source> {
+    pub param:    ParamExpr<'source>,
+    /// Where the argument is stored while the body runs
+    pub location: Register,
+}
+
+impl CompTimeSize<'_> for Local<'_> {
+    fn number_bytes(&self, ctx: &ProgramContext) -> ByteSize {
+        self.param.number_bytes(ctx)
+    }
+}
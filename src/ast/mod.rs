@@ -1,17 +1,23 @@
 pub use ast::Ast;
 pub use ast_util::scope::ProgramContext;
+use doc::signature_to_source;
+pub use doc::write_docs;
+pub use expr::SmallValue;
 
-pub(self) use crate::{
-    errors::{compile_error, CheckError, CheckResult, ParsingError},
-    lexer::{FileContext, Token},
+use crate::{
+    errors::{compile_error, CheckError, CheckResult, Diagnostic, ParsingError},
+    lexer::{FileContext, Span, Token},
     parser::{Parsable, Parser},
 };
 
+#[allow(clippy::module_inception)]
 mod ast;
 
+mod doc;
+
 mod ast_util;
-pub(self) use ast_util::{
-    register::{free_register, Register},
+use ast_util::{
+    register::Register,
     scope::{
         change_indentation, current_padding, next_scope, reset_scope_and_indent, IndentationChange,
         ScopeId, Symbol,
@@ -20,28 +26,42 @@ pub(self) use ast_util::{
 };
 
 mod ast_traits;
-pub(self) use ast_traits::{AstNode, CompTimeSize};
+use ast_traits::{AstNode, CompTimeSize};
 
 mod expr;
-pub(self) use expr::{Expr, SmallValue};
+use expr::Expr;
+
+mod format_string;
+use format_string::{FormatSegment, FormatString};
+
+mod list;
+use list::List;
+
+mod local;
+use local::Local;
+
+mod move_expr;
+use move_expr::{parse_move_from, parse_move_into, Destination, Move};
 
 mod raw_token;
-pub(self) use raw_token::RawToken;
+use raw_token::RawToken;
 
 mod ident;
-pub(self) use ident::Ident;
+use ident::Ident;
 
 mod assign;
-pub(self) use assign::{parse_assign, Assign};
+use assign::{parse_assign, Assign};
 
 mod fn_call;
-pub(self) use fn_call::{argument_list::ArgumentList, fn_call::FnCall};
+use fn_call::{argument_list::ArgumentList, fn_call::FnCall};
 
 mod fn_def;
-pub(self) use fn_def::{FnDef, FnSignature, Param, ParamExpr, ParamList};
+use fn_def::{
+    check_overloads, source_text, FnDef, FnSignature, MetaType, Param, ParamExpr, ParamList,
+};
 
 mod listcontent;
-pub(self) use listcontent::ListContent;
+use listcontent::ListContent;
 
 mod typeexpr;
-pub(self) use typeexpr::{find_type, Type, TypeFn};
+use typeexpr::{find_type, ComptimeError, Type};
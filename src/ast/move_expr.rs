@@ -0,0 +1,263 @@
+use super::*;
+
+/// `destination <- source` or `source -> destination`
+#[derive(Debug, Clone)]
+pub struct Move<'source> {
+    pub source: Box<Expr<'source>>,
+    pub destination: Destination<'source>,
+    pub span: Span,
+}
+
+/// What can be moved into
+#[derive(Debug, Clone, PartialEq)]
+pub enum Destination<'source> {
+    Alias(&'source str, Span),
+    Register(Register, Span),
+}
+
+impl PartialEq for Move<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        // Ignore span
+        self.source == other.source && self.destination == other.destination
+    }
+}
+
+impl std::fmt::Display for Destination<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Destination::Alias(name, _) => write!(f, "{name}"),
+            Destination::Register(register, _) => write!(f, "{register}"),
+        }
+    }
+}
+
+impl Destination<'_> {
+    pub fn span(&self) -> Span {
+        match self {
+            Destination::Alias(_, span) | Destination::Register(_, span) => span.clone(),
+        }
+    }
+
+    fn number_bytes(&self, ctx: &ProgramContext, scope_stack: &[ScopeId]) -> CheckResult<ByteSize> {
+        match self {
+            Destination::Alias(name, span) => alias_size(ctx, scope_stack, name, span),
+            Destination::Register(register, _) => Ok(register.number_bytes(ctx)),
+        }
+    }
+}
+
+impl<'source> AstNode<'source> for Move<'source> {
+    fn build_context(&self, _ctx: &mut ProgramContext<'source>, _scope_stack: &mut Vec<ScopeId>) {}
+
+    fn check_and_emit<Output: std::io::Write>(
+        &self,
+        output: &mut Output,
+        ctx: &ProgramContext<'source>,
+        scope_stack: &mut Vec<ScopeId>,
+    ) -> CheckResult<()> {
+        if let Expr::Move(inner) = self.source.as_ref() {
+            // `a -> b -> c` moves `a` to `b` and then `b` to `c`
+            inner.check_and_emit(output, ctx, scope_stack)?;
+        }
+        let source_size = value_size(ctx, scope_stack, &self.source)?;
+        let destination_size = self.destination.number_bytes(ctx, scope_stack)?;
+        // Untyped literals allow any size, but don't fit into all of them
+        if let (Expr::SmallValue(value, _), Some(largest)) =
+            (self.source.as_ref(), destination_size.largest())
+        {
+            if value.min_bytes() > largest {
+                return Err(Diagnostic::error(
+                    self.span.clone(),
+                    format!("Value doesn't fit into {destination_size}"),
+                )
+                .with_code("size-mismatch")
+                .with_label(
+                    self.source.span(),
+                    format!("needs at least {}b", value.min_bytes()),
+                )
+                .with_label(
+                    self.destination.span(),
+                    format!("this is {destination_size}"),
+                )
+                .into());
+            }
+        }
+        let overlap = source_size.overlap(&destination_size);
+        let widened = match (source_size.largest(), destination_size.smallest()) {
+            (Some(source), Some(destination)) if source < destination => Some(destination),
+            _ => None,
+        };
+        let size = match (overlap.as_ref().map(ByteSize::largest), widened) {
+            // Use all of the space there is
+            (Some(Some(size)), _) => size,
+            // Smaller values are widened
+            (None, Some(size)) => size,
+            (Some(None), _) => {
+                return Err(Diagnostic::error(
+                    self.span.clone(),
+                    "Unknown size of move".to_string(),
+                )
+                .with_code("unknown-size")
+                .with_primary_label("neither side has a known size")
+                .into())
+            }
+            (None, None) => {
+                return Err(Diagnostic::error(
+                    self.span.clone(),
+                    format!("Size mismatch: can't move {source_size} into {destination_size}"),
+                )
+                .with_code("size-mismatch")
+                .with_label(self.source.span(), format!("this is {source_size}"))
+                .with_label(
+                    self.destination.span(),
+                    format!("this is {destination_size}"),
+                )
+                .into())
+            }
+        };
+        writeln!(
+            output,
+            "{}move {}b {} -> {}",
+            current_padding(),
+            size,
+            operand(&self.source, size),
+            self.destination
+        )?;
+        Ok(())
+    }
+}
+
+/// How the value of `expr` is written in a move of `size` bytes
+fn operand(expr: &Expr, size: usize) -> String {
+    match expr {
+        Expr::SmallValue(value, _) => value.to_hex(size),
+        Expr::Register(register, _) => register.to_string(),
+        Expr::Local(local, _) => local.location.to_string(),
+        Expr::FnCall(FnCall { name, .. }) => name.to_string(),
+        Expr::Move(inner) => inner.destination.to_string(),
+        expr => format!("{expr:?}"),
+    }
+}
+
+/// Size of a value that is moved. Names refer to aliases
+fn value_size(ctx: &ProgramContext, scope_stack: &[ScopeId], expr: &Expr) -> CheckResult<ByteSize> {
+    match expr {
+        Expr::FnCall(FnCall { name, args, span }) if args.is_empty() => {
+            alias_size(ctx, scope_stack, name, span)
+        }
+        Expr::Move(inner) => inner.destination.number_bytes(ctx, scope_stack),
+        Expr::SmallValue(..) | Expr::Register(..) | Expr::Local(..) | Expr::Bytes(..) => {
+            Ok(expr.number_bytes(ctx))
+        }
+        _ => compile_error(
+            expr.span(),
+            "invalid-move",
+            "Only values, registers and aliases can be moved".to_string(),
+        ),
+    }
+}
+
+/// Size of the value a name in scope stands for
+fn alias_size(
+    ctx: &ProgramContext,
+    scope_stack: &[ScopeId],
+    name: &str,
+    span: &Span,
+) -> CheckResult<ByteSize> {
+    let symbol = scope_stack
+        .last()
+        .and_then(|scope| ctx.lookup(name, *scope).last().copied());
+    match symbol {
+        Some(symbol) => value_size(ctx, scope_stack, &symbol.value),
+        None => compile_error(
+            span.clone(),
+            "unknown-alias",
+            format!("No alias named {name} in this scope"),
+        ),
+    }
+}
+
+/// Should be called when on the token after `<-`, with the destination that
+/// came before it
+pub fn parse_move_into<'source>(
+    parser: &mut Parser<'source>,
+    destination: Destination<'source>,
+    start: usize,
+) -> Result<Expr<'source>, ParsingError<'source>> {
+    let source = Expr::parse(parser)?;
+    Ok(Expr::Move(Move {
+        span: start..source.span().end,
+        source: source.into(),
+        destination,
+    }))
+}
+
+/// Should be called when on `->`, with the source that came before it
+pub fn parse_move_from<'source>(
+    parser: &mut Parser<'source>,
+    source: Expr<'source>,
+) -> Result<Expr<'source>, ParsingError<'source>> {
+    parser.advance(); // Skip '->'
+    let destination = match parser.current_token {
+        Some(Ok(Token::Ident)) => {
+            Destination::Alias(parser.current_slice, parser.current_span.clone())
+        }
+        Some(Ok(Token::Reg(number))) => {
+            Destination::Register(Register(number), parser.current_span.clone())
+        }
+        Some(Ok(ref token)) => {
+            return Err(ParsingError::unexpected_token(
+                "move destination",
+                parser.lexer.extras.clone(),
+                parser.current_span.clone(),
+                token.clone(),
+                vec![Token::Ident, Token::Reg(0)],
+            ))
+        }
+        Some(Err(ref err)) => {
+            return Err(ParsingError::TokenError(
+                err.clone(),
+                parser.lexer.extras.clone(),
+                parser.current_span.clone(),
+            ))
+        }
+        None => {
+            return Err(ParsingError::AbruptEof(
+                "move destination",
+                parser.lexer.extras.clone(),
+                parser.current_span.clone(),
+                vec![Token::Ident, Token::Reg(0)],
+            ))
+        }
+    };
+    parser.advance();
+    Ok(Expr::Move(Move {
+        span: source.span().start..destination.span().end,
+        source: source.into(),
+        destination,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::compile_to_string;
+
+    #[test]
+    fn move_sizes() {
+        // Smaller values are widened
+        let output = compile_to_string("x = 0u16, x <- 1u8,").unwrap();
+        assert_eq!(output.lines().map(str::trim).collect::<Vec<_>>(), [
+            "move 2b 0x0000 -> x",
+            "move 2b 0x0001 -> x",
+        ]);
+
+        for source in [
+            "x = 0u8, x <- 300,",
+            "x = 0u16, x <- 70000,",
+            "x = 0u8, x <- 2u16,",
+        ] {
+            let errors = compile_to_string(source).unwrap_err();
+            assert_eq!(errors[0].code, Some("size-mismatch"), "{source}");
+        }
+    }
+}
@@ -1,18 +1,30 @@
 use super::*;
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone)]
 pub enum RawToken<'source> {
-    Ident(Ident<'source>),
-    Symbol(char),
+    Ident(Ident<'source>, Span),
+    Symbol(char, Span),
 
     Expr(Expr<'source>),
 }
 
+impl PartialEq for RawToken<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        // Ignore spans
+        match (self, other) {
+            (RawToken::Ident(a, _), RawToken::Ident(b, _)) => a == b,
+            (RawToken::Symbol(a, _), RawToken::Symbol(b, _)) => a == b,
+            (RawToken::Expr(a), RawToken::Expr(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
 impl CompTimeSize<'_> for RawToken<'_> {
     fn number_bytes(&self, ctx: &ProgramContext) -> ByteSize {
         match self {
-            Self::Ident(_) => ByteSize::AnySize,
-            Self::Symbol(_) => ByteSize::Exact(1),
+            Self::Ident(..) => ByteSize::AnySize,
+            Self::Symbol(..) => ByteSize::Exact(1),
             Self::Expr(expr) => expr.number_bytes(ctx),
         }
     }
@@ -21,61 +33,94 @@ impl CompTimeSize<'_> for RawToken<'_> {
 impl std::fmt::Debug for RawToken<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            RawToken::Ident(ident) => write!(f, "{:?}", ident),
-            RawToken::Symbol(symbol) => write!(f, "Symbol({:?})", symbol),
+            RawToken::Ident(ident, _) => write!(f, "{:?}", ident),
+            RawToken::Symbol(symbol, _) => write!(f, "Symbol({:?})", symbol),
             RawToken::Expr(expr) => write!(f, "{:?}", expr),
         }
     }
 }
 
 impl<'source> RawToken<'source> {
-    pub fn from_token(token: &Token, slice: &'source str) -> Self {
+    pub fn from_token(token: &Token, slice: &'source str, span: Span) -> Self {
         match token {
-            Token::Ident => RawToken::Ident(Ident(slice)),
-            Token::Int(val) => RawToken::Expr(Expr::SmallValue(SmallValue::Untyped(*val as u64))),
-            _ => {
-                assert_eq!(slice.len(), 1);
-                RawToken::Symbol(slice.chars().next().unwrap())
+            // Keywords are plain words when used as arguments
+            Token::Ident | Token::KeywordFn | Token::KeywordType | Token::KeywordWhere => {
+                RawToken::Ident(Ident(slice), span)
+            }
+            Token::Int(val) => RawToken::Expr(Expr::SmallValue(val.clone(), span)),
+            Token::Reg(number) => RawToken::Expr(Expr::Register(Register(*number), span)),
+            Token::String(bytes) => RawToken::Expr(Expr::Bytes(bytes.clone(), span)),
+            // The lexer only produces symbols of a single character
+            Token::Symbol(symbol) => RawToken::Symbol(symbol.chars().next().unwrap_or(' '), span),
+            Token::FormatString(_) => {
+                unreachable!("Format strings contain expressions and are parsed by Expr::parse")
+            }
+            Token::LeftArrow | Token::RightArrow => {
+                unreachable!("Moves are parsed by Expr::parse")
             }
+            Token::Newline | Token::DocComment(_) | Token::Comment | Token::BlockComment => {
+                unreachable!("{token} is never passed to the parser")
+            }
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        match self {
+            RawToken::Ident(_, span) | RawToken::Symbol(_, span) => span.clone(),
+            RawToken::Expr(expr) => expr.span(),
         }
     }
 }
 
 impl<'source> Parsable<'source> for RawToken<'source> {
     fn parse(parser: &mut Parser<'source>) -> Result<RawToken<'source>, ParsingError<'source>> {
+        let span = parser.current_span.clone();
         match parser.current_token.as_ref() {
-            Some(Ok(Token::String)) => {
-                Ok(RawToken::from_token(&Token::String, parser.current_slice))
+            Some(Ok(Token::Symbol("(" | "{" | "[") | Token::FormatString(_))) => {
+                Ok(RawToken::Expr(Expr::parse(parser)?))
             }
-            Some(Ok(Token::Int(val))) => Ok(RawToken::from_token(
-                &Token::Int(*val),
-                parser.current_slice,
+            Some(Ok(
+                token @ (Token::String(_)
+                | Token::Int(_)
+                | Token::Reg(_)
+                | Token::Ident
+                | Token::KeywordFn
+                | Token::KeywordType
+                | Token::KeywordWhere
+                | Token::Symbol(_)),
+            )) => Ok(RawToken::from_token(token, parser.current_slice, span)),
+            Some(Ok(token)) => Err(ParsingError::unexpected_token(
+                "raw_token",
+                parser.lexer.extras.clone(),
+                span,
+                token.clone(),
+                vec![
+                    Token::String(Box::default()),
+                    Token::Int(SmallValue::Untyped(0)),
+                    Token::Ident,
+                    Token::KeywordFn,
+                    Token::Symbol("("),
+                    Token::Symbol("<AnySymbol>"),
+                ],
+            )),
+            Some(Err(err)) => Err(ParsingError::TokenError(
+                err.clone(),
+                parser.lexer.extras.clone(),
+                span,
             )),
-            Some(Ok(Token::Ident)) => Ok(RawToken::Ident(Ident(parser.current_slice))),
-            Some(Ok(Token::KeywordFn)) => Ok(RawToken::Ident(Ident("fn"))),
-            Some(Ok(Token::Symbol("("))) => Ok(RawToken::Expr(Expr::parse(parser)?)),
-            Some(Ok(Token::Symbol(symbol))) => {
-                assert_eq!(symbol.len(), 1, "Symbol should be a single character");
-                Ok(RawToken::Symbol(symbol.chars().next().unwrap()))
-            }
-            Some(Err(())) => Err(ParsingError::TokenError(format!(
-                "Lexer error in {file}@{line}",
-                file = parser.lexer.extras.filename,
-                line = parser.lexer.extras.line
-            ))),
             None => Err(ParsingError::AbruptEof(
                 "raw_token",
                 parser.lexer.extras.clone(),
+                span,
                 vec![
-                    Token::String,
-                    Token::Int(0),
+                    Token::String(Box::default()),
+                    Token::Int(SmallValue::Untyped(0)),
                     Token::Ident,
                     Token::KeywordFn,
                     Token::Symbol("("),
                     Token::Symbol("<AnySymbol>"),
                 ],
             )),
-            other => panic!("Impossible: {other:?}"),
         }
     }
 }
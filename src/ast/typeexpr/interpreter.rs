@@ -0,0 +1,152 @@
+use std::{collections::HashMap, fmt};
+
+use super::*;
+
+/// A value computed at compile time
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    Int(u64),
+    Bytes(Box<[u8]>),
+    /// Anything else, by its source code
+    Code(String),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Bool(value) => write!(f, "{value}"),
+            Value::Int(value) => write!(f, "{value}"),
+            Value::Bytes(bytes) => write!(f, "{}", String::from_utf8_lossy(bytes)),
+            Value::Code(code) => write!(f, "{code}"),
+        }
+    }
+}
+
+impl Value {
+    /// The value of an argument a type is checked on
+    pub fn of_arg(ctx: &ProgramContext, arg: &Expr) -> Value {
+        match arg {
+            Expr::SmallValue(value, _) => Value::Int(value.as_u64()),
+            Expr::Bytes(bytes, _) => Value::Bytes(bytes.clone()),
+            arg => Value::Code(source_text(ctx, arg.span()).to_string()),
+        }
+    }
+}
+
+/// Why code run at compile time didn't return a value
+#[derive(Debug, Clone, PartialEq)]
+pub enum ComptimeError {
+    /// Raised with `comptime-error`, with the message
+    Raised(String),
+    /// The code can't be run, with an explanation
+    Invalid(String),
+}
+
+/// Runs code at compile time. Knows `true`, `false`, the bound names and
+///
+/// - `comptime-error ..parts`, which fails with the parts as the message
+/// - `if condition {then} {else}`
+/// - `not a`, `and a b` and `or a b`
+/// - `equal a b` and `less a b`
+pub struct Interpreter<'a, 'source> {
+    ctx:    &'a ProgramContext<'source>,
+    locals: HashMap<&'source str, Value>,
+}
+
+impl<'a, 'source> Interpreter<'a, 'source> {
+    pub fn new(ctx: &'a ProgramContext<'source>) -> Self {
+        Self {
+            ctx,
+            locals: HashMap::new(),
+        }
+    }
+
+    pub fn bind(&mut self, name: &'source str, value: Value) {
+        self.locals.insert(name, value);
+    }
+
+    /// The value of the last element of the block
+    pub fn eval_block(&mut self, block: &ListContent) -> Result<Value, ComptimeError> {
+        let mut value = None;
+        for element in &block.elements {
+            value = Some(self.eval(element)?);
+        }
+        value.ok_or_else(|| ComptimeError::Invalid("an empty block has no value".to_string()))
+    }
+
+    pub fn eval(&mut self, expr: &Expr) -> Result<Value, ComptimeError> {
+        match expr {
+            Expr::SmallValue(value, _) => Ok(Value::Int(value.as_u64())),
+            Expr::Bytes(bytes, _) => Ok(Value::Bytes(bytes.clone())),
+            Expr::List(list) if !list.mutable => self.eval_block(&list.elements),
+            Expr::FnCall(FnCall { name, args, .. }) => self.call(name, args),
+            expr => Err(ComptimeError::Invalid(format!(
+                "`{}` can't be run at compile time",
+                source_text(self.ctx, expr.span())
+            ))),
+        }
+    }
+
+    fn call(&mut self, name: &str, args: &[RawToken]) -> Result<Value, ComptimeError> {
+        if let (Some(value), []) = (self.locals.get(name), args) {
+            return Ok(value.clone());
+        }
+        match (name, args) {
+            ("true", []) => Ok(Value::Bool(true)),
+            ("false", []) => Ok(Value::Bool(false)),
+            ("comptime-error", parts) => {
+                let mut message = String::new();
+                for part in parts {
+                    message += &self.eval_arg(part)?.to_string();
+                }
+                Err(ComptimeError::Raised(message))
+            }
+            ("if", [condition, then, otherwise]) => {
+                if self.eval_bool(condition)? {
+                    self.eval_arg(then)
+                } else {
+                    self.eval_arg(otherwise)
+                }
+            }
+            ("not", [a]) => Ok(Value::Bool(!self.eval_bool(a)?)),
+            ("and", [a, b]) => Ok(Value::Bool(self.eval_bool(a)? && self.eval_bool(b)?)),
+            ("or", [a, b]) => Ok(Value::Bool(self.eval_bool(a)? || self.eval_bool(b)?)),
+            ("equal", [a, b]) => Ok(Value::Bool(self.eval_arg(a)? == self.eval_arg(b)?)),
+            ("less", [a, b]) => Ok(Value::Bool(self.eval_int(a)? < self.eval_int(b)?)),
+            (name, args) => Err(ComptimeError::Invalid(format!(
+                "there is no compile time function `{name}` taking {} arguments",
+                args.len()
+            ))),
+        }
+    }
+
+    fn eval_arg(&mut self, arg: &RawToken) -> Result<Value, ComptimeError> {
+        match arg {
+            RawToken::Ident(Ident(name), _) => self.call(name, &[]),
+            RawToken::Expr(expr) => self.eval(expr),
+            RawToken::Symbol(..) => Err(ComptimeError::Invalid(format!(
+                "`{}` is not a value",
+                source_text(self.ctx, arg.span())
+            ))),
+        }
+    }
+
+    fn eval_bool(&mut self, arg: &RawToken) -> Result<bool, ComptimeError> {
+        match self.eval_arg(arg)? {
+            Value::Bool(value) => Ok(value),
+            value => Err(ComptimeError::Invalid(format!(
+                "expected true or false, found `{value}`"
+            ))),
+        }
+    }
+
+    fn eval_int(&mut self, arg: &RawToken) -> Result<u64, ComptimeError> {
+        match self.eval_arg(arg)? {
+            Value::Int(value) => Ok(value),
+            value => Err(ComptimeError::Invalid(format!(
+                "expected a number, found `{value}`"
+            ))),
+        }
+    }
+}
@@ -4,7 +4,10 @@ pub use type_utils::find_type;
 mod r#type;
 pub use r#type::Type;
 
-mod type_fn;
-pub use type_fn::TypeFn;
+mod predicate;
+pub use predicate::{parse_predicate, Predicate};
 
-pub(self) use super::*;
+mod interpreter;
+pub use interpreter::{ComptimeError, Interpreter, Value};
+
+use super::*;
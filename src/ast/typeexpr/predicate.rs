@@ -0,0 +1,144 @@
+use super::*;
+
+/// How deep predicates can be evaluated inside each other before the types
+/// are taken to check each other endlessly
+const MAX_PREDICATE_DEPTH: usize = 32;
+
+/// `([a: Any]) { ... }` in a `type`, run at compile time on every argument of
+/// the type. Returns `true` if the argument has the type
+#[derive(Debug, Clone)]
+pub struct Predicate<'source> {
+    pub param: ParamExpr<'source>,
+    pub body:  ListContent<'source>,
+}
+
+impl PartialEq for Predicate<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.param == other.param && self.body == other.body
+    }
+}
+
+impl<'source> Predicate<'source> {
+    pub fn evaluate(
+        &self,
+        ctx: &ProgramContext<'source>,
+        arg: &Expr,
+    ) -> Result<bool, ComptimeError> {
+        let depth = ctx.predicate_depth.get();
+        if depth >= MAX_PREDICATE_DEPTH {
+            return Err(ComptimeError::Invalid(
+                "the types check each other endlessly".to_string(),
+            ));
+        }
+        ctx.predicate_depth.set(depth + 1);
+        let result = self.evaluate_unguarded(ctx, arg);
+        ctx.predicate_depth.set(depth);
+        result
+    }
+
+    fn evaluate_unguarded(
+        &self,
+        ctx: &ProgramContext<'source>,
+        arg: &Expr,
+    ) -> Result<bool, ComptimeError> {
+        // The parameter's own types are checked first
+        for typename in &self.param.typenames {
+            if let Some(meta) = MetaType::from_name(typename) {
+                if !meta.matches_expr(arg) {
+                    return Ok(false);
+                }
+                continue;
+            }
+            let Some(type_) = find_type(ctx, typename) else {
+                return Err(ComptimeError::Invalid(format!("unknown type {typename}")));
+            };
+            if type_.size.overlap(&arg.number_bytes(ctx)).is_none() {
+                return Ok(false);
+            }
+            if let Some(predicate) = &type_.predicate {
+                if !predicate.evaluate(ctx, arg)? {
+                    return Ok(false);
+                }
+            }
+        }
+        let mut interpreter = Interpreter::new(ctx);
+        if let Some(name) = self.param.name {
+            interpreter.bind(name, Value::of_arg(ctx, arg));
+        }
+        match interpreter.eval_block(&self.body)? {
+            Value::Bool(matches) => Ok(matches),
+            value => Err(ComptimeError::Invalid(format!(
+                "returned `{value}` instead of true or false"
+            ))),
+        }
+    }
+}
+
+/// Should be called when on the `(` or `[` of the parameter
+pub fn parse_predicate<'source>(
+    parser: &mut Parser<'source>,
+) -> Result<Predicate<'source>, ParsingError<'source>> {
+    let start = parser.current_span.start;
+    let mut params = ParamList::parse(parser)?;
+    let params_span = parser.span_from(start);
+    let param = match params.pop() {
+        Some(Param::ParamExpr(param))
+            if params.is_empty() && param.name.is_some() && !param.is_block() =>
+        {
+            param
+        }
+        _ => {
+            return Err(ParsingError::InvalidType(
+                "a type takes a single named parameter, like `([a: Any])`",
+                parser.lexer.extras.clone(),
+                params_span,
+            ))
+        }
+    };
+    let body = ListContent::parse(parser)?;
+    Ok(Predicate { param, body })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{compile_to_string, emitted_after};
+
+    #[test]
+    fn evaluate_predicates() {
+        let types = concat!(
+            "B = type 1,\n",
+            "Small = type ([a]) {\n",
+            "    if (less a 10) { true } { comptime-error a \" is too large\", false }\n",
+            "},\n",
+            "Broken = type ([a]) { a },\n",
+        );
+        let notes = |calls: &str| {
+            let errors = compile_to_string(&format!("{types}{calls}")).unwrap_err();
+            assert_eq!(errors[0].code, Some("signature-mismatch"));
+            errors[0].notes.clone()
+        };
+
+        let output =
+            compile_to_string(&format!("{types}f = fn [a: B + Small] {{}}, f 5,")).unwrap();
+        assert_eq!(emitted_after(&output, "| Function call: f"), [
+            "push 1b Untyped(5)",
+            "call f",
+        ]);
+        assert_eq!(notes("f = fn [a: B + Small] {}, f 12,"), [concat!(
+            "`f = fn [a: B + Small]` doesn't match:\n",
+            "argument 1: `12` is not a Small: 12 is too large",
+        )]);
+        assert_eq!(notes("f = fn [a: B + Broken] {}, f 5,"), [concat!(
+            "`f = fn [a: B + Broken]` doesn't match:\n",
+            "argument 1: the check of Broken failed: returned `5` instead of true or false",
+        )]);
+        let endless = concat!(
+            "P = type ([a: B + Q]) { true }, Q = type ([a: B + P]) { true },\n",
+            "f = fn [a: B + P] {}, f 1,",
+        );
+        assert_eq!(notes(endless), [concat!(
+            "`f = fn [a: B + P]` doesn't match:\n",
+            "argument 1: the check of P failed: the types check each other endlessly",
+        )]);
+    }
+}
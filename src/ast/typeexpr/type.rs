@@ -1,38 +1,216 @@
 use super::*;
 
-#[derive(Debug, Clone, PartialEq)]
-pub struct Type {
-    pub size:    ByteSize,
-    pub type_fn: Option<TypeFn>,
+/// `type 4`, `type 1..8`, `type 2..=4` or `type 1..8 default 4`, optionally
+/// followed by a predicate like `([a: Any]) { ... }`
+#[derive(Debug, Clone)]
+pub struct Type<'source> {
+    pub size:      ByteSize,
+    /// The size of arguments whose size isn't otherwise known, in the range
+    pub default:   Option<usize>,
+    pub predicate: Option<Predicate<'source>>,
+    pub span:      Span,
 }
 
-impl Parsable<'_> for Type {
-    fn parse<'source>(parser: &mut Parser<'source>) -> Result<Type, ParsingError<'source>> {
-        match parser.current_token {
-            // TODO: Add support for type ranges and type check fns
-            Some(Ok(Token::Int(int))) => {
+impl PartialEq for Type<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        // Ignore span
+        self.size == other.size
+            && self.default == other.default
+            && self.predicate == other.predicate
+    }
+}
+
+impl std::fmt::Display for Type<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.size {
+            ByteSize::Exact(size) => write!(f, "type {size}")?,
+            ByteSize::Range(range) => write!(f, "type {}..={}", range.start(), range.end())?,
+            ByteSize::AnySize => write!(f, "type")?,
+        }
+        if let Some(default) = self.default {
+            write!(f, " default {default}")?;
+        }
+        if let Some(predicate) = &self.predicate {
+            let name = predicate.param.name.unwrap_or_default();
+            write!(f, " ([{name}]) {{ .. }}")?;
+        }
+        Ok(())
+    }
+}
+
+impl<'source> Parsable<'source> for Type<'source> {
+    /// Should be called when on the `type` keyword
+    fn parse(parser: &mut Parser<'source>) -> Result<Type<'source>, ParsingError<'source>> {
+        let start = parser.current_span.start;
+        parser.advance(); // Skip 'type'
+        if starts_predicate(parser) {
+            return Ok(Type {
+                size:      ByteSize::AnySize,
+                default:   None,
+                predicate: Some(parse_predicate(parser)?),
+                span:      parser.span_from(start),
+            });
+        }
+        let size_start = parser.current_span.start;
+        let min = parse_size(parser, "type")?;
+        let size = if matches!(parser.current_token, Some(Ok(Token::Symbol(".")))) {
+            parser.advance(); // Skip the first '.'
+            if !matches!(parser.current_token, Some(Ok(Token::Symbol(".")))) {
+                return Err(unexpected(parser, "type range", vec![Token::Symbol(".")]));
+            }
+            parser.advance();
+            let inclusive = matches!(parser.current_token, Some(Ok(Token::Symbol("="))));
+            if inclusive {
                 parser.advance();
-                Ok(Type {
-                    size:    ByteSize::Exact(int as usize),
-                    type_fn: None,
-                })
             }
-            None => Err(ParsingError::AbruptEof(
-                "type",
-                parser.lexer.extras.clone(),
-                vec![Token::Int(0)],
-            )),
-            Some(Ok(ref token)) => Err(ParsingError::UnexpectedToken(
-                "type",
-                parser.lexer.extras.clone(),
-                token.clone(),
-                vec![Token::Int(0)],
-            )),
-            Some(Err(())) => Err(ParsingError::TokenError(format!(
-                "Parsing error in {file}@{line}",
-                file = parser.lexer.extras.filename,
-                line = parser.lexer.extras.line
-            ))),
+            let end = parse_size(parser, "type range")?;
+            let max = if inclusive {
+                Some(end)
+            } else {
+                end.checked_sub(1)
+            };
+            match max {
+                Some(max) if max == min => ByteSize::Exact(min),
+                Some(max) if max > min => ByteSize::Range(min..=max),
+                _ => {
+                    return Err(ParsingError::InvalidType(
+                        "the range contains no sizes",
+                        parser.lexer.extras.clone(),
+                        parser.span_from(size_start),
+                    ))
+                }
+            }
+        } else {
+            ByteSize::Exact(min)
+        };
+        let default = if matches!(parser.current_token, Some(Ok(Token::Ident)))
+            && parser.current_slice == "default"
+        {
+            let default_start = parser.current_span.start;
+            parser.advance(); // Skip 'default'
+            let default = parse_size(parser, "default size")?;
+            if size.overlap(&ByteSize::Exact(default)).is_none() {
+                return Err(ParsingError::InvalidType(
+                    "the default size is not one of the sizes of the type",
+                    parser.lexer.extras.clone(),
+                    parser.span_from(default_start),
+                ));
+            }
+            Some(default)
+        } else {
+            None
+        };
+        let predicate = if starts_predicate(parser) {
+            Some(parse_predicate(parser)?)
+        } else {
+            None
+        };
+        Ok(Type {
+            size,
+            default,
+            predicate,
+            span: parser.span_from(start),
+        })
+    }
+}
+
+/// Whether the current token is the start of the parameter of a predicate
+fn starts_predicate(parser: &Parser) -> bool {
+    matches!(parser.current_token, Some(Ok(Token::Symbol("(" | "["))))
+}
+
+/// A number of bytes, should be called when on the integer
+fn parse_size<'source>(
+    parser: &mut Parser<'source>,
+    what: &'source str,
+) -> Result<usize, ParsingError<'source>> {
+    let Some(Ok(Token::Int(ref int))) = parser.current_token else {
+        return Err(unexpected(parser, what, vec![Token::Int(
+            SmallValue::Untyped(0),
+        )]));
+    };
+    let size = int.as_u64() as usize;
+    parser.advance();
+    Ok(size)
+}
+
+/// Error for the current token, which isn't one of `expected`
+fn unexpected<'source>(
+    parser: &Parser<'source>,
+    what: &'source str,
+    expected: Vec<Token<'source>>,
+) -> ParsingError<'source> {
+    match parser.current_token {
+        None => ParsingError::AbruptEof(
+            what,
+            parser.lexer.extras.clone(),
+            parser.current_span.clone(),
+            expected,
+        ),
+        Some(Ok(ref token)) => ParsingError::unexpected_token(
+            what,
+            parser.lexer.extras.clone(),
+            parser.current_span.clone(),
+            token.clone(),
+            expected,
+        ),
+        Some(Err(ref err)) => ParsingError::TokenError(
+            err.clone(),
+            parser.lexer.extras.clone(),
+            parser.current_span.clone(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{compile_to_string, emitted_after};
+
+    #[test]
+    fn sizes_ranges_and_defaults() {
+        let types = "A = type 4, B = type 1..8, C = type 2..=4 default 4, D = type 2..3,\n";
+        let output = compile_to_string(types).unwrap();
+        for (name, size) in [
+            ("A", "4b"),
+            ("B", "(1..=7)b"),
+            ("C", "(2..=4)b"),
+            ("D", "2b"),
+        ] {
+            assert_eq!(emitted_after(&output, &format!("|| Type {name}")), [
+                format!("{name} = type {size}")
+            ]);
+        }
+
+        let call = |call: &str, marker: &str| {
+            let source = format!("{types}f = fn [c: C] {{}},\ng = fn [b: B] {{}},\n{call}");
+            let output = compile_to_string(&source).unwrap();
+            emitted_after(&output, marker).join("\n")
+        };
+        // The default size
+        assert_eq!(
+            call("f 3,", "| Function call: f"),
+            "push 4b Untyped(3)\ncall f"
+        );
+        // The smallest size that fits, inlined so the pop matches
+        let pop = "pop {size} -> r0       | 0th argument: b";
+        for (arg, push, size) in [
+            ("3", "push 1b Untyped(3)", "1b"),
+            ("300", "push 2b Untyped(300)", "2b"),
+            ("4u16", "push 2b Word(4)", "2b"),
+            ("r1", "push 7b r1", "7b"),
+        ] {
+            assert_eq!(
+                call(&format!("g {arg},"), "| Inlined call: g"),
+                format!("{push}\n{}", pop.replace("{size}", size)),
+            );
+        }
+
+        for source in [
+            "C = type 2..=4 default 2, f = fn [c: C] {}, f 0x123456,",
+            "B = type 1..3, g = fn [b: B] {}, g 0x123456,",
+        ] {
+            let errors = compile_to_string(source).unwrap_err();
+            assert_eq!(errors[0].code, Some("size-mismatch"), "{source}");
         }
     }
 }
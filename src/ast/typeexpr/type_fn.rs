@@ -1,3 +0,0 @@
-use super::*;
-
-pub type TypeFn = fn(&Expr) -> bool;
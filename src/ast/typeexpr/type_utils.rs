@@ -1,5 +1,8 @@
 pub use super::*;
 
-pub fn find_type<'source>(ctx: &'source ProgramContext, typename: &str) -> Option<&'source Type> {
+pub fn find_type<'a, 'source>(
+    ctx: &'a ProgramContext<'source>,
+    typename: &str,
+) -> Option<&'a Type<'source>> {
     ctx.types.get(typename)
 }
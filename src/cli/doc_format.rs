@@ -0,0 +1,36 @@
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum DocFormat {
+    Markdown,
+    Html,
+}
+
+impl DocFormat {
+    /// File extension of the generated pages
+    pub fn extension(&self) -> &'static str {
+        match self {
+            DocFormat::Markdown => "md",
+            DocFormat::Html => "html",
+        }
+    }
+}
+
+impl std::fmt::Display for DocFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            DocFormat::Markdown => write!(f, "markdown"),
+            DocFormat::Html => write!(f, "html"),
+        }
+    }
+}
+
+impl std::str::FromStr for DocFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "markdown" | "md" => Ok(Self::Markdown),
+            "html" => Ok(Self::Html),
+            _ => Err(format!("Unknown documentation format: {}", s)),
+        }
+    }
+}
@@ -0,0 +1,28 @@
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum MessageFormat {
+    /// Diagnostics with source snippets, for people
+    Human,
+    /// One JSON object per diagnostic and line, for tools
+    Json,
+}
+
+impl std::fmt::Display for MessageFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            MessageFormat::Human => write!(f, "human"),
+            MessageFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+impl std::str::FromStr for MessageFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "human" => Ok(Self::Human),
+            "json" => Ok(Self::Json),
+            _ => Err(format!("Unknown message format: {}", s)),
+        }
+    }
+}
@@ -1,14 +1,23 @@
 use std::path::PathBuf;
 
-use clap::{arg, builder::TypedValueParser as _, command, Args, Parser as ArgParser, Subcommand};
+use clap::{builder::TypedValueParser as _, Args, Parser as ArgParser, Subcommand};
+pub use doc_format::DocFormat;
+pub use message_format::MessageFormat;
 pub use verbosity::Verbosity;
 
 use crate::layers::Layer;
 
+mod doc_format;
+mod message_format;
 mod verbosity;
 
 #[derive(ArgParser)]
-#[command(version, about, long_about = None)]
+#[command(
+    version,
+    about,
+    long_about = None,
+    after_help = "Exits with 1 if the input has errors, and with 70 if the compiler crashed"
+)]
 pub struct Cli {
     /// Subcommands
     #[command(subcommand)]
@@ -27,6 +36,8 @@ pub struct Cli {
 pub enum CliSubCommand {
     Expand(ExpansionArgs),
     Run(ExpansionArgs),
+    /// Generate documentation for the given programs
+    Doc(DocArgs),
 }
 
 #[derive(Args)]
@@ -58,7 +69,36 @@ pub struct ExpansionArgs {
     /// Emit the entire symbol table to a file
     #[arg(long, value_name = "FILE")]
     pub symbols: Option<PathBuf>,
+
+    /// How to print errors to stderr
+    #[arg(
+        long,
+        default_value_t = MessageFormat::Human,
+        value_parser = clap::builder::PossibleValuesParser::new(["human", "json"])
+            .map(|s| s.parse::<MessageFormat>().unwrap()),
+    )]
+    pub message_format: MessageFormat,
 }
 
 #[derive(Args)]
 pub struct RunArgs {}
+
+#[derive(Args)]
+pub struct DocArgs {
+    /// Input source files. Can be omitted to read from stdin
+    pub input_files: Option<Vec<PathBuf>>,
+
+    /// Format of the generated pages
+    #[arg(
+        short, long,
+        default_value_t = DocFormat::Markdown,
+        value_parser = clap::builder::PossibleValuesParser::new(["markdown", "md", "html"])
+            .map(|s| s.parse::<DocFormat>().unwrap()),
+    )]
+    pub format: DocFormat,
+
+    /// Directory to write one page per input file to. Can be omitted to write
+    /// to stdout
+    #[arg(short, long, value_name = "DIR")]
+    pub output: Option<PathBuf>,
+}
@@ -1,24 +1,19 @@
 use std::io;
 
-use super::SyntaxErrorContext;
+use super::Diagnostic;
 
 pub type CheckResult<T> = Result<T, CheckError>;
 
 pub enum CheckError {
-    CompileError(SyntaxErrorContext, String),
+    /// Boxed to keep results small
+    CompileError(Box<Diagnostic>),
     EmitError(io::Error),
 }
 
 impl std::fmt::Display for CheckError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
-            CheckError::CompileError(context, msg) => write!(
-                f,
-                "[Compile Error]<{file} {line}> {msg}\n\t\"{line_content}\"",
-                file = context.filename,
-                line = context.line,
-                line_content = context.line_content
-            ),
+            CheckError::CompileError(diagnostic) => write!(f, "{}", diagnostic),
             CheckError::EmitError(error) => write!(f, "{}", error),
         }
     }
@@ -29,3 +24,9 @@ impl From<io::Error> for CheckError {
         CheckError::EmitError(error)
     }
 }
+
+impl From<Diagnostic> for CheckError {
+    fn from(diagnostic: Diagnostic) -> Self {
+        CheckError::CompileError(Box::new(diagnostic))
+    }
+}
@@ -1,27 +1,86 @@
 use std::io;
 
-use super::{CheckError, CheckResult, ParsingError};
-use crate::lexer::FileContext;
+use super::{CheckError, CheckResult, Diagnostic, ParsingError};
+use crate::lexer::{FileContext, Span};
 
 pub type CompileResult<'source, T> = Result<T, CompileError<'source>>;
 
+#[allow(clippy::enum_variant_names)]
 pub enum CompileError<'source> {
-    ParsingError(ParsingError<'source>),
+    /// All syntax errors of a file, never empty
+    ParsingErrors(Vec<ParsingError<'source>>),
+    /// All errors about the definitions of a file, never empty
+    DefinitionErrors(Vec<Diagnostic>, FileContext<'source>),
+    CheckError(CheckError, FileContext<'source>),
     IoError(io::Error),
 }
 
+impl CompileError<'_> {
+    /// Render the error for the terminal, with source snippets where possible
+    pub fn render(&self, color: bool) -> String {
+        match self {
+            CompileError::ParsingErrors(errors) => errors
+                .iter()
+                .map(|error| error.diagnostic().render(error.context(), color) + "\n")
+                .collect(),
+            CompileError::DefinitionErrors(diagnostics, context) => diagnostics
+                .iter()
+                .map(|diagnostic| diagnostic.render(context, color) + "\n")
+                .collect(),
+            CompileError::CheckError(CheckError::CompileError(diagnostic), context) => {
+                diagnostic.render(context, color)
+            }
+            CompileError::CheckError(CheckError::EmitError(error), _)
+            | CompileError::IoError(error) => format!("error: {}\n", error),
+        }
+    }
+
+    /// The error as JSON objects, one per line, see [`Diagnostic::to_json`]
+    pub fn to_json(&self) -> String {
+        match self {
+            CompileError::ParsingErrors(errors) => errors
+                .iter()
+                .map(|error| error.diagnostic().to_json(error.context()))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            CompileError::DefinitionErrors(diagnostics, context) => diagnostics
+                .iter()
+                .map(|diagnostic| diagnostic.to_json(context))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            CompileError::CheckError(CheckError::CompileError(diagnostic), context) => {
+                diagnostic.to_json(context)
+            }
+            CompileError::CheckError(CheckError::EmitError(error), _)
+            | CompileError::IoError(error) => Diagnostic::io_error_json(error),
+        }
+    }
+}
+
 impl std::fmt::Display for CompileError<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
-            CompileError::ParsingError(error) => write!(f, "{}", error),
+            CompileError::ParsingErrors(errors) => {
+                for error in errors {
+                    writeln!(f, "{}", error)?;
+                }
+                Ok(())
+            }
+            CompileError::DefinitionErrors(diagnostics, _) => {
+                for diagnostic in diagnostics {
+                    writeln!(f, "{}", diagnostic)?;
+                }
+                Ok(())
+            }
+            CompileError::CheckError(error, _) => write!(f, "{}", error),
             CompileError::IoError(error) => write!(f, "{}", error),
         }
     }
 }
 
-impl<'source> From<ParsingError<'source>> for CompileError<'source> {
-    fn from(e: ParsingError<'source>) -> Self {
-        CompileError::ParsingError(e)
+impl<'source> From<Vec<ParsingError<'source>>> for CompileError<'source> {
+    fn from(e: Vec<ParsingError<'source>>) -> Self {
+        CompileError::ParsingErrors(e)
     }
 }
 
@@ -31,24 +90,6 @@ impl From<io::Error> for CompileError<'_> {
     }
 }
 
-pub fn compile_error<T>(context: FileContext, msg: String) -> CheckResult<T> {
-    Err(CheckError::CompileError(
-        SyntaxErrorContext {
-            line: context.line,
-            filename: context.filename,
-            line_content: context
-                .source
-                .lines()
-                .nth(context.line)
-                .expect("Not a valid source line")
-                .to_string(),
-        },
-        msg,
-    ))
-}
-
-pub struct SyntaxErrorContext {
-    pub filename: String,
-    pub line: usize,
-    pub line_content: String,
+pub fn compile_error<T>(span: Span, code: &'static str, msg: String) -> CheckResult<T> {
+    Err(Diagnostic::error(span, msg).with_code(code).into())
 }
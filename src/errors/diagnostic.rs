@@ -0,0 +1,351 @@
+use std::fmt::Write as _;
+
+use crate::lexer::{FileContext, Span};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Label {
+    pub span:    Span,
+    pub message: String,
+}
+
+/// An error or warning pointing into the source, rendered like
+///
+/// ```text
+/// error: Function not found anywhere: prnt
+///  --> file:3:1
+///   |
+/// 3 | prnt 4,
+///   | ^^^^^^ not defined
+///   |
+///   = help: ...
+/// ```
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity:  Severity,
+    /// Short stable name of the kind of problem, e.g. `unexpected-token`
+    pub code:      Option<&'static str>,
+    pub message:   String,
+    /// Where the problem is
+    pub primary:   Label,
+    /// Other places that are related to the problem
+    pub secondary: Vec<Label>,
+    pub notes:     Vec<String>,
+    pub help:      Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn error(span: Span, message: String) -> Self {
+        Self::new(Severity::Error, span, message)
+    }
+
+    pub fn warning(span: Span, message: String) -> Self {
+        Self::new(Severity::Warning, span, message)
+    }
+
+    fn new(severity: Severity, span: Span, message: String) -> Self {
+        Self {
+            severity,
+            code: None,
+            message,
+            primary: Label {
+                span,
+                message: String::new(),
+            },
+            secondary: vec![],
+            notes: vec![],
+            help: vec![],
+        }
+    }
+
+    pub fn with_code(mut self, code: &'static str) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    /// Set the text shown under the primary span
+    pub fn with_primary_label(mut self, message: impl Into<String>) -> Self {
+        self.primary.message = message.into();
+        self
+    }
+
+    pub fn with_label(mut self, span: Span, message: impl Into<String>) -> Self {
+        self.secondary.push(Label {
+            span,
+            message: message.into(),
+        });
+        self
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.help.push(help.into());
+        self
+    }
+
+    /// Render the diagnostic with source snippets. `color` enables ANSI colors
+    pub fn render(&self, context: &FileContext, color: bool) -> String {
+        let style = |code: &str, text: &str| {
+            if color {
+                format!("\x1b[{code}m{text}\x1b[0m")
+            } else {
+                text.to_string()
+            }
+        };
+        let severity_code = match self.severity {
+            Severity::Error => "1;31",
+            Severity::Warning => "1;33",
+        };
+
+        let mut labels = vec![(&self.primary, true)];
+        labels.extend(self.secondary.iter().map(|label| (label, false)));
+        labels.sort_by_key(|(label, _)| label.span.start);
+
+        let (line, column) = context.location(self.primary.span.start);
+        let last_line = labels
+            .iter()
+            .map(|(label, _)| context.location(label.span.start).0)
+            .max()
+            .unwrap_or(line);
+        let width = last_line.to_string().len();
+        let gutter = style("1;34", &format!("{} |", " ".repeat(width)));
+
+        let mut out = String::new();
+        let severity = match self.code {
+            Some(code) => format!("{}[{code}]", self.severity),
+            None => self.severity.to_string(),
+        };
+        let _ = writeln!(
+            out,
+            "{}{}",
+            style(severity_code, &severity),
+            style("1", &format!(": {}", self.message))
+        );
+        let _ = writeln!(
+            out,
+            "{}{} {}:{line}:{column}",
+            " ".repeat(width),
+            style("1;34", "-->"),
+            context.filename
+        );
+        let _ = writeln!(out, "{gutter}");
+
+        let mut previous_line = None;
+        for (label, is_primary) in labels {
+            let (line, column) = context.location(label.span.start);
+            let content = context.line_content(line).replace('\t', " ");
+            if previous_line != Some(line) {
+                if previous_line.is_some_and(|previous| line > previous + 1) {
+                    let _ = writeln!(out, "{}", style("1;34", "..."));
+                }
+                let _ = writeln!(
+                    out,
+                    "{} {content}",
+                    style("1;34", &format!("{line:>width$} |"))
+                );
+            }
+            previous_line = Some(line);
+
+            // Underline up to the end of the span or the end of its first line
+            let line_chars = content.chars().count();
+            let (end_line, end_column) = context.location(label.span.end);
+            let end_column = if end_line == line {
+                end_column
+            } else {
+                line_chars + 1
+            };
+            let underline_width = end_column.saturating_sub(column).max(1);
+            let (marker, code) = if is_primary {
+                ('^', severity_code)
+            } else {
+                ('-', "1;34")
+            };
+            let underline = format!(
+                "{}{}",
+                marker.to_string().repeat(underline_width),
+                if label.message.is_empty() {
+                    String::new()
+                } else {
+                    format!(" {}", label.message)
+                }
+            );
+            let _ = writeln!(
+                out,
+                "{gutter} {}{}",
+                " ".repeat(column - 1),
+                style(code, &underline)
+            );
+        }
+
+        if !self.notes.is_empty() || !self.help.is_empty() {
+            let _ = writeln!(out, "{gutter}");
+        }
+        for (kind, texts) in [("note", &self.notes), ("help", &self.help)] {
+            for text in texts {
+                // Align continuation lines with the text after `= note: `
+                let indent = " ".repeat(width + kind.len() + 5);
+                let _ = writeln!(
+                    out,
+                    "{} {} {}",
+                    " ".repeat(width),
+                    style("1;34", "="),
+                    format_args!(
+                        "{}: {}",
+                        style("1", kind),
+                        text.replace('\n', &format!("\n{indent}"))
+                    )
+                );
+            }
+        }
+        out
+    }
+
+    /// The diagnostic as a single line JSON object
+    pub fn to_json(&self, context: &FileContext) -> String {
+        format!(
+            "{{\"severity\":{},\"code\":{},\"message\":{},\"file\":{},\"primary\":{},\"related\":\
+             [{}],\"notes\":[{}],\"help\":[{}]}}",
+            json_string(&self.severity.to_string()),
+            self.code.map_or("null".to_string(), json_string),
+            json_string(&self.message),
+            json_string(&context.filename),
+            label_to_json(&self.primary, context),
+            self.secondary
+                .iter()
+                .map(|label| label_to_json(label, context))
+                .collect::<Vec<_>>()
+                .join(","),
+            self.notes
+                .iter()
+                .map(|note| json_string(note))
+                .collect::<Vec<_>>()
+                .join(","),
+            self.help
+                .iter()
+                .map(|help| json_string(help))
+                .collect::<Vec<_>>()
+                .join(","),
+        )
+    }
+}
+
+impl Diagnostic {
+    /// JSON object for errors that have no location in the source
+    pub fn io_error_json(error: &std::io::Error) -> String {
+        format!(
+            "{{\"severity\":\"error\",\"code\":\"io-error\",\"message\":{},\"file\":null,\"\
+             primary\":null,\"related\":[],\"notes\":[],\"help\":[]}}",
+            json_string(&error.to_string())
+        )
+    }
+}
+
+fn label_to_json(label: &Label, context: &FileContext) -> String {
+    let (line_start, column_start) = context.location(label.span.start);
+    let (line_end, column_end) = context.location(label.span.end);
+    format!(
+        "{{\"message\":{},\"byte_start\":{},\"byte_end\":{},\"line_start\":{line_start},\"\
+         column_start\":{column_start},\"line_end\":{line_end},\"column_end\":{column_end}}}",
+        json_string(&label.message),
+        label.span.start,
+        label.span.end,
+    )
+}
+
+/// Quote and escape a string for JSON
+fn json_string(text: &str) -> String {
+    let mut json = String::with_capacity(text.len() + 2);
+    json.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.severity, self.message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_labels_notes_and_help() {
+        let source = "f = fn 5 { },\n\nf 4,\n";
+        let context = FileContext {
+            filename: "test_render".to_string(),
+            source,
+            line: 1,
+        };
+        let diagnostic = Diagnostic::error(15..18, "Function signature mismatch".to_string())
+            .with_primary_label("no match")
+            .with_label(4..12, "candidate")
+            .with_note("first\nsecond")
+            .with_help("help text");
+        assert_eq!(
+            diagnostic.render(&context, false),
+            "error: Function signature mismatch
+ --> test_render:3:1
+  |
+1 | f = fn 5 { },
+  |     -------- candidate
+...
+3 | f 4,
+  | ^^^ no match
+  |
+  = note: first
+          second
+  = help: help text
+"
+        );
+    }
+
+    #[test]
+    fn json_locations_and_escaping() {
+        let source = "x = 1,\nf \"a\",\n";
+        let context = FileContext {
+            filename: "dir/test.exas".to_string(),
+            source,
+            line: 1,
+        };
+        let diagnostic = Diagnostic::error(7..12, "Bad \"call\"".to_string())
+            .with_code("signature-mismatch")
+            .with_label(0..1, "defined\there");
+        assert_eq!(
+            diagnostic.to_json(&context),
+            r#"{"severity":"error","code":"signature-mismatch","message":"Bad \"call\"","file":"dir/test.exas","primary":{"message":"","byte_start":7,"byte_end":12,"line_start":2,"column_start":1,"line_end":2,"column_end":6},"related":[{"message":"defined\there","byte_start":0,"byte_end":1,"line_start":1,"column_start":1,"line_end":1,"column_end":2}],"notes":[],"help":[]}"#
+        );
+    }
+}
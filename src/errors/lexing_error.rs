@@ -0,0 +1,43 @@
+use std::ops::Range;
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub enum LexingError {
+    #[default]
+    UnknownToken,
+    /// A `/..` without matching `../`
+    UnterminatedBlockComment,
+    /// An integer literal that doesn't fit its size, with the largest value
+    /// that would
+    IntegerTooLarge(u64),
+    /// A register above `r255`
+    RegisterTooLarge,
+    /// A digit that is not valid in the radix of an integer literal
+    InvalidDigit(u32),
+    /// A `0b` or `0o` prefix without digits after it, with the radix
+    MissingDigits(u32),
+    /// An unknown escape sequence in a string literal, at the given range
+    /// relative to the start of the literal
+    InvalidEscape(Range<usize>),
+    /// A `{` or `}` in a format string without a partner, at the given range
+    /// relative to the start of the literal
+    InvalidFormatBrace(Range<usize>),
+}
+
+impl std::fmt::Display for LexingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LexingError::UnknownToken => write!(f, "Unknown token"),
+            LexingError::UnterminatedBlockComment => write!(f, "Block comment is never closed"),
+            LexingError::IntegerTooLarge(_) => write!(f, "Integer literal is too large"),
+            LexingError::RegisterTooLarge => write!(f, "Register number is too large"),
+            LexingError::InvalidDigit(radix) => {
+                write!(f, "Invalid digit in base {radix} integer literal")
+            }
+            LexingError::MissingDigits(radix) => {
+                write!(f, "Base {radix} integer literal has no digits")
+            }
+            LexingError::InvalidEscape(_) => write!(f, "Invalid escape sequence in string literal"),
+            LexingError::InvalidFormatBrace(_) => write!(f, "Unmatched brace in format string"),
+        }
+    }
+}
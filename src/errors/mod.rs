@@ -1,7 +1,11 @@
 mod check_result;
 mod compile_result;
+mod diagnostic;
+mod lexing_error;
 mod parsing_error;
 
 pub use check_result::{CheckError, CheckResult};
-pub use compile_result::{compile_error, CompileError, CompileResult, SyntaxErrorContext};
+pub use compile_result::{compile_error, CompileError, CompileResult};
+pub use diagnostic::{Diagnostic, Severity};
+pub use lexing_error::LexingError;
 pub use parsing_error::ParsingError;
@@ -1,39 +1,236 @@
-use crate::lexer::{FileContext, Token};
+use super::{Diagnostic, LexingError};
+use crate::lexer::{FileContext, Span, Token};
 
 pub enum ParsingError<'source> {
-    AbruptEof(&'source str, FileContext<'source>, Vec<Token<'source>>),
-    UnexpectedToken(
+    AbruptEof(
         &'source str,
         FileContext<'source>,
-        Token<'source>,
+        Span,
         Vec<Token<'source>>,
     ),
-    TokenError(String),
+    /// Boxed, it is the largest variant by far
+    UnexpectedToken(Box<UnexpectedToken<'source>>),
+    TokenError(LexingError, FileContext<'source>, Span),
+    /// A malformed `[name: Type]` parameter, with an explanation of what is
+    /// wrong
+    InvalidParam(&'static str, FileContext<'source>, Span),
+    /// A `where` clause constraint that doesn't fit the parameters, with an
+    /// explanation of what is wrong
+    InvalidConstraint(&'static str, FileContext<'source>, Span),
+    /// A `type` whose sizes don't make sense, with an explanation of what is
+    /// wrong
+    InvalidType(&'static str, FileContext<'source>, Span),
+}
+
+/// A token that doesn't fit where it is, see [`ParsingError::unexpected_token`]
+pub struct UnexpectedToken<'source> {
+    /// What was being parsed
+    pub what:     &'source str,
+    pub context:  FileContext<'source>,
+    pub span:     Span,
+    pub found:    Token<'source>,
+    pub expected: Vec<Token<'source>>,
+}
+
+impl<'source> ParsingError<'source> {
+    pub fn unexpected_token(
+        what: &'source str,
+        context: FileContext<'source>,
+        span: Span,
+        found: Token<'source>,
+        expected: Vec<Token<'source>>,
+    ) -> Self {
+        ParsingError::UnexpectedToken(Box::new(UnexpectedToken {
+            what,
+            context,
+            span,
+            found,
+            expected,
+        }))
+    }
+}
+
+impl ParsingError<'_> {
+    pub fn context(&self) -> &FileContext<'_> {
+        match self {
+            ParsingError::AbruptEof(_, context, ..)
+            | ParsingError::TokenError(_, context, _)
+            | ParsingError::InvalidParam(_, context, _)
+            | ParsingError::InvalidConstraint(_, context, _)
+            | ParsingError::InvalidType(_, context, _) => context,
+            ParsingError::UnexpectedToken(error) => &error.context,
+        }
+    }
+
+    pub fn span(&self) -> &Span {
+        match self {
+            ParsingError::AbruptEof(_, _, span, _)
+            | ParsingError::TokenError(_, _, span)
+            | ParsingError::InvalidParam(_, _, span)
+            | ParsingError::InvalidConstraint(_, _, span)
+            | ParsingError::InvalidType(_, _, span) => span,
+            ParsingError::UnexpectedToken(error) => &error.span,
+        }
+    }
+
+    pub fn diagnostic(&self) -> Diagnostic {
+        match self {
+            ParsingError::AbruptEof(what, _, span, expected) => Diagnostic::error(
+                span.clone(),
+                format!("Unexpected end of file while parsing {what}"),
+            )
+            .with_code("unexpected-eof")
+            .with_primary_label(format!("expected {}", one_of(expected))),
+            ParsingError::UnexpectedToken(error) => {
+                let UnexpectedToken {
+                    what,
+                    context,
+                    span,
+                    found: token,
+                    expected,
+                } = error.as_ref();
+                let found = match token {
+                    // These already show their source
+                    Token::Symbol(_)
+                    | Token::KeywordFn
+                    | Token::KeywordType
+                    | Token::KeywordWhere
+                    | Token::LeftArrow
+                    | Token::RightArrow => token.to_string(),
+                    _ => format!("{token} `{}`", &context.source[span.clone()]),
+                };
+                Diagnostic::error(
+                    span.clone(),
+                    format!("Unexpected {found} while parsing {what}"),
+                )
+                .with_code("unexpected-token")
+                .with_primary_label(format!("expected {}", one_of(expected)))
+            }
+            ParsingError::TokenError(error @ LexingError::UnknownToken, _, span) => {
+                Diagnostic::error(span.clone(), error.to_string())
+                    .with_code("invalid-token")
+                    .with_primary_label("not a valid token")
+            }
+            ParsingError::TokenError(error @ LexingError::UnterminatedBlockComment, _, span) => {
+                Diagnostic::error(span.start..span.start + 3, error.to_string())
+                    .with_code("unterminated-comment")
+                    .with_primary_label("comment opened here")
+                    .with_help("Close the comment with a matching `../`")
+            }
+            ParsingError::TokenError(error @ LexingError::IntegerTooLarge(max), _, span) => {
+                Diagnostic::error(span.clone(), error.to_string())
+                    .with_code("integer-too-large")
+                    .with_primary_label(format!("the largest value of this size is {max}"))
+            }
+            ParsingError::TokenError(error @ LexingError::RegisterTooLarge, _, span) => {
+                Diagnostic::error(span.clone(), error.to_string())
+                    .with_code("register-too-large")
+                    .with_primary_label(format!("the last register is r{}", u8::MAX))
+            }
+            ParsingError::TokenError(error @ LexingError::InvalidDigit(_), _, span) => {
+                Diagnostic::error(span.clone(), error.to_string())
+                    .with_code("invalid-digit")
+                    .with_primary_label("not a valid integer")
+            }
+            ParsingError::TokenError(error @ LexingError::MissingDigits(_), _, span) => {
+                Diagnostic::error(span.clone(), error.to_string())
+                    .with_code("missing-digits")
+                    .with_primary_label("expected digits after the prefix")
+            }
+            ParsingError::TokenError(error @ LexingError::InvalidEscape(escape), _, span) => {
+                Diagnostic::error(
+                    span.start + escape.start..span.start + escape.end,
+                    error.to_string(),
+                )
+                .with_code("invalid-escape")
+                .with_primary_label("unknown escape sequence")
+                .with_help(
+                    "Valid escapes are `\\\"`, `\\\\`, `\\0`, `\\b`, `\\f`, `\\n`, `\\r`, `\\t` \
+                     and `\\uXXXX`",
+                )
+            }
+            ParsingError::TokenError(error @ LexingError::InvalidFormatBrace(brace), _, span) => {
+                Diagnostic::error(
+                    span.start + brace.start..span.start + brace.end,
+                    error.to_string(),
+                )
+                .with_code("invalid-format-brace")
+                .with_primary_label("unmatched brace")
+                .with_help("Write `{{` or `}}` for a literal brace")
+            }
+            ParsingError::InvalidParam(explanation, _, span) => {
+                Diagnostic::error(span.clone(), "Invalid parameter".to_string())
+                    .with_code("invalid-param")
+                    .with_primary_label(*explanation)
+                    .with_help(
+                        "Parameters are written as `[name: Type]`, `[name: Type + OtherType]`, \
+                         `[name]` or `[:Type]`",
+                    )
+            }
+            ParsingError::InvalidConstraint(explanation, _, span) => {
+                Diagnostic::error(span.clone(), "Invalid constraint".to_string())
+                    .with_code("invalid-constraint")
+                    .with_primary_label(*explanation)
+                    .with_help("Constraints are written as `where name: Type + OtherType, ..`")
+            }
+            ParsingError::InvalidType(explanation, _, span) => {
+                Diagnostic::error(span.clone(), "Invalid type".to_string())
+                    .with_code("invalid-type")
+                    .with_primary_label(*explanation)
+                    .with_help(
+                        "Types are written as `type 4`, `type 1..8`, `type 2..=4` or `type 1..8 \
+                         default 4`",
+                    )
+            }
+        }
+    }
+}
+
+/// `a`, `b` or `c`
+fn one_of(tokens: &[Token]) -> String {
+    let tokens = tokens.iter().map(Token::to_string).collect::<Vec<_>>();
+    match tokens.split_last() {
+        None => "nothing".to_string(),
+        Some((last, [])) => last.clone(),
+        Some((last, rest)) => format!("one of {} or {last}", rest.join(", ")),
+    }
 }
 
 impl std::fmt::Display for ParsingError<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let context = self.context();
+        let (line, column) = context.location(self.span().start);
+        let file = &context.filename;
         match self {
-            ParsingError::AbruptEof(msg, context, expected) => {
+            ParsingError::AbruptEof(msg, _, _, expected) => {
                 write!(
                     f,
-                    "AbruptEof(expected one of {:?} at {file}@{line}): {msg}",
+                    "AbruptEof(expected one of {:?} at {file}@{line}:{column}): {msg}",
                     expected,
-                    file = context.filename,
-                    line = context.line
                 )
             }
-            ParsingError::UnexpectedToken(msg, context, token, expected) => {
+            ParsingError::UnexpectedToken(error) => {
                 write!(
                     f,
-                    "UnexpectedToken({:?}, expected one of <{:?}> at {file}@{line}): {msg}",
-                    token,
-                    expected,
-                    file = context.filename,
-                    line = context.line
+                    "UnexpectedToken({:?}, expected one of <{:?}> at {file}@{line}:{column}): {}",
+                    error.found, error.expected, error.what,
                 )
             }
-            ParsingError::TokenError(error) => write!(f, "TokenError({:?})", error),
+            ParsingError::TokenError(error, ..) => {
+                write!(f, "TokenError(at {file}@{line}:{column}): {error}")
+            }
+            ParsingError::InvalidParam(explanation, ..) => {
+                write!(f, "InvalidParam(at {file}@{line}:{column}): {explanation}")
+            }
+            ParsingError::InvalidConstraint(explanation, ..) => {
+                write!(
+                    f,
+                    "InvalidConstraint(at {file}@{line}:{column}): {explanation}"
+                )
+            }
+            ParsingError::InvalidType(explanation, ..) => {
+                write!(f, "InvalidType(at {file}@{line}:{column}): {explanation}")
+            }
         }
     }
 }
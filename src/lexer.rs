@@ -1,4 +1,11 @@
-use logos::{Logos, Skip};
+use std::num::IntErrorKind;
+
+use logos::{FilterResult, Logos, Skip};
+
+use crate::{ast::SmallValue, errors::LexingError};
+
+/// Byte range of a token or node in the source
+pub type Span = logos::Span;
 
 #[derive(Debug, Clone)]
 pub struct FileContext<'source> {
@@ -7,15 +14,49 @@ pub struct FileContext<'source> {
     pub line:     usize,
 }
 
+impl FileContext<'_> {
+    /// Line and column (both starting at 1) of a byte offset into the source
+    pub fn location(&self, offset: usize) -> (usize, usize) {
+        let before = &self.source[..offset.min(self.source.len())];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+        (line, before[line_start..].chars().count() + 1)
+    }
+
+    /// The full source line (starting at 1) without its line break
+    pub fn line_content(&self, line: usize) -> &str {
+        self.source.lines().nth(line - 1).unwrap_or_default()
+    }
+}
+
+/// Part of a format string
+#[derive(Debug, PartialEq, Clone)]
+pub enum FormatChunk {
+    /// Decoded text
+    Text(Box<[u8]>, Span),
+    /// Span of the source of an embedded expression, without the braces
+    Expr(Span),
+}
+
 #[derive(Logos, Debug, PartialEq, Clone)]
 #[logos(extras = FileContext<'s>)]
+#[logos(error = LexingError)]
 #[logos(skip r"[ \t\r\f]+")] // Ignore whitespace
 pub enum Token<'source> {
     #[regex(r"[-[:alpha:][:digit:]]*")]
     Ident,
 
-    #[regex(r"[0-9]+", |lex| lex.slice().parse::<u32>().expect("Wrong"))]
-    Int(u32),
+    /// `42`, `1_000`, `0x1F`, `0b1010`, `0o755` or `755o`, optionally with
+    /// a size suffix: `u8` (or `b`), `u16`, `u32` or `u64`
+    #[regex(r"[0-9][0-9_]*(u8|u16|u32|u64|b|o)?", int_literal)]
+    #[regex(r"0x[0-9a-fA-F_]+(u8|u16|u32|u64)?", int_literal)]
+    #[regex(r"0b[01_]+(u8|u16|u32|u64|b)?", int_literal)]
+    #[regex(r"0o[0-7_]+(u8|u16|u32|u64|b)?", int_literal)]
+    Int(SmallValue),
+
+    /// `r0`, `r1`, ..
+    #[regex(r"r[0-9]+", |lex| lex.slice()[1..].parse::<u8>().map_err(|_| LexingError::RegisterTooLarge))]
+    Reg(u8),
 
     #[token(r"fn")]
     KeywordFn,
@@ -23,20 +64,257 @@ pub enum Token<'source> {
     #[token(r"type")]
     KeywordType,
 
-    #[regex(r#""([^"\\]|\\["\\bnfrt]|u\p{hexdigit}{4})*""#)]
-    String,
+    #[token(r"where")]
+    KeywordWhere,
+
+    /// UTF-8 bytes of a string literal, with escapes decoded
+    #[regex(r#""([^"\\]|\\(.|\n))*""#, string_literal)]
+    String(Box<[u8]>),
+
+    /// `f"The answer is {answer}"`
+    #[regex(r#"f"([^"\\]|\\(.|\n))*""#, format_string)]
+    FormatString(Box<[FormatChunk]>),
 
     #[regex(r###"[^0-9a-zA-Z\p{whitespace}|]"###)]
     Symbol(&'source str),
 
+    /// `destination <- source`
+    #[token("<-")]
+    LeftArrow,
+
+    /// `source -> destination`
+    #[token("->")]
+    RightArrow,
+
     #[regex(r"\n", |lex| {
         lex.extras.line += 1;
         Skip
     })]
     Newline,
 
+    #[regex(r"\|\|[^\n]*", doc_comment)]
+    DocComment(&'source str),
+
     #[regex(r"\|[^\n]*", logos::skip)]
+    #[regex(r"//[^\n]*", logos::skip)]
     Comment,
+
+    #[token("/..", block_comment)]
+    BlockComment,
+}
+
+impl std::fmt::Display for Token<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Ident => write!(f, "identifier"),
+            Token::Int(_) => write!(f, "integer"),
+            Token::Reg(_) => write!(f, "register"),
+            Token::KeywordFn => write!(f, "`fn`"),
+            Token::KeywordType => write!(f, "`type`"),
+            Token::KeywordWhere => write!(f, "`where`"),
+            Token::String(_) => write!(f, "string literal"),
+            Token::FormatString(_) => write!(f, "format string"),
+            Token::Symbol(symbol) => write!(f, "`{symbol}`"),
+            Token::LeftArrow => write!(f, "`<-`"),
+            Token::RightArrow => write!(f, "`->`"),
+            Token::Newline => write!(f, "newline"),
+            Token::DocComment(_) => write!(f, "doc comment"),
+            Token::Comment | Token::BlockComment => write!(f, "comment"),
+        }
+    }
+}
+
+/// Value of an integer literal, sized if it has a suffix
+fn int_literal<'source>(
+    lex: &mut logos::Lexer<'source, Token<'source>>,
+) -> Result<SmallValue, LexingError> {
+    let text = lex.slice();
+    let (radix, text) = match text.get(..2) {
+        Some("0x") => (16, &text[2..]),
+        Some("0b") => (2, &text[2..]),
+        Some("0o") => (8, &text[2..]),
+        _ => (10, text),
+    };
+    let suffix_start = text
+        .find(|c: char| c == 'u' || (radix != 16 && c.is_ascii_alphabetic()))
+        .unwrap_or(text.len());
+    let (digits, suffix) = text.split_at(suffix_start);
+    let radix = if suffix == "o" { 8 } else { radix };
+    if digits.replace('_', "").is_empty() {
+        return Err(LexingError::MissingDigits(radix));
+    }
+
+    let value =
+        u64::from_str_radix(&digits.replace('_', ""), radix).map_err(|error| {
+            match error.kind() {
+                IntErrorKind::PosOverflow => LexingError::IntegerTooLarge(u64::MAX),
+                _ => LexingError::InvalidDigit(radix),
+            }
+        })?;
+    let too_large = |max: u64| LexingError::IntegerTooLarge(max);
+    Ok(match suffix {
+        "" | "o" => SmallValue::Untyped(value),
+        "u8" | "b" => SmallValue::Byte(value.try_into().map_err(|_| too_large(u8::MAX.into()))?),
+        "u16" => SmallValue::Word(value.try_into().map_err(|_| too_large(u16::MAX.into()))?),
+        "u32" => SmallValue::DWord(value.try_into().map_err(|_| too_large(u32::MAX.into()))?),
+        _ => SmallValue::QWord(value),
+    })
+}
+
+/// UTF-8 bytes of a string literal
+fn string_literal<'source>(
+    lex: &mut logos::Lexer<'source, Token<'source>>,
+) -> Result<Box<[u8]>, LexingError> {
+    let slice = lex.slice();
+    lex.extras.line += slice.matches('\n').count();
+    Ok(unescape(&slice[1..slice.len() - 1], 1)?
+        .into_bytes()
+        .into_boxed_slice())
+}
+
+/// Split an `f"..."` literal into text and the spans of the `{expr}`s in it.
+/// `{{` and `}}` stand for literal braces
+fn format_string<'source>(
+    lex: &mut logos::Lexer<'source, Token<'source>>,
+) -> Result<Box<[FormatChunk]>, LexingError> {
+    let slice = lex.slice();
+    let start = lex.span().start;
+    lex.extras.line += slice.matches('\n').count();
+
+    // Offsets are relative to the start of the literal, after the `f"`
+    let content = &slice[2..slice.len() - 1];
+    let mut chunks = vec![];
+    let mut text = String::new();
+    let mut text_start = 0;
+    let mut chunk_start = 0;
+    let mut chars = content.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '{' | '}' if chars.peek().map(|&(_, next)| next) == Some(c) => {
+                text += &unescape(&content[chunk_start..i], 2 + chunk_start)?;
+                text.push(c);
+                chars.next();
+                chunk_start = i + 2;
+            }
+            '{' => {
+                let Some(end) = content[i..].find('}').map(|end| i + end) else {
+                    return Err(LexingError::InvalidFormatBrace(2 + i..3 + i));
+                };
+                text += &unescape(&content[chunk_start..i], 2 + chunk_start)?;
+                if !text.is_empty() {
+                    let bytes = std::mem::take(&mut text).into_bytes().into_boxed_slice();
+                    chunks.push(FormatChunk::Text(
+                        bytes,
+                        start + 2 + text_start..start + 2 + i,
+                    ));
+                }
+                chunks.push(FormatChunk::Expr(start + 3 + i..start + 2 + end));
+                while chars.next_if(|&(j, _)| j <= end).is_some() {}
+                chunk_start = end + 1;
+                text_start = end + 1;
+            }
+            '}' => return Err(LexingError::InvalidFormatBrace(2 + i..3 + i)),
+            _ => {}
+        }
+    }
+    text += &unescape(&content[chunk_start..], 2 + chunk_start)?;
+    if !text.is_empty() {
+        chunks.push(FormatChunk::Text(
+            text.into_bytes().into_boxed_slice(),
+            start + 2 + text_start..start + 2 + content.len(),
+        ));
+    }
+    Ok(chunks.into_boxed_slice())
+}
+
+/// Decode the escapes `\"`, `\\`, `\0`, `\b`, `\f`, `\n`, `\r`, `\t` and
+/// `\uXXXX` of `text`, which starts `offset` bytes into its literal
+fn unescape(text: &str, offset: usize) -> Result<String, LexingError> {
+    let mut string = String::with_capacity(text.len());
+    let mut chars = text.char_indices();
+    while let Some((backslash, c)) = chars.next() {
+        if c != '\\' {
+            string.push(c);
+            continue;
+        }
+        let Some((index, escape)) = chars.next() else {
+            // Only possible in format strings, where `\{` is cut off
+            return Err(LexingError::InvalidEscape(
+                offset + backslash..offset + backslash + 2,
+            ));
+        };
+        let invalid_escape =
+            |len: usize| LexingError::InvalidEscape(offset + backslash..offset + index + len);
+        string.push(match escape {
+            '"' => '"',
+            '\\' => '\\',
+            '0' => '\0',
+            'b' => '\u{8}',
+            'f' => '\u{c}',
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            'u' => {
+                let after_u = &text[index + 1..];
+                let hex_len = after_u
+                    .chars()
+                    .take(4)
+                    .take_while(char::is_ascii_hexdigit)
+                    .count();
+                let code = u32::from_str_radix(&after_u[..hex_len], 16)
+                    .ok()
+                    .filter(|_| hex_len == 4);
+                match code.and_then(char::from_u32) {
+                    Some(c) => {
+                        chars.nth(3);
+                        c
+                    }
+                    None => return Err(invalid_escape(1 + hex_len)),
+                }
+            }
+            _ => return Err(invalid_escape(escape.len_utf8())),
+        });
+    }
+    Ok(string)
+}
+
+/// The text of a `||` doc comment, without the leading `|| `
+fn doc_comment<'source>(lex: &mut logos::Lexer<'source, Token<'source>>) -> &'source str {
+    let text = &lex.slice()[2..];
+    text.strip_prefix(' ').unwrap_or(text).trim_end()
+}
+
+/// Skip a (possibly nested) `/.. ../` block comment, keeping track of the
+/// newlines inside it
+fn block_comment<'source>(
+    lex: &mut logos::Lexer<'source, Token<'source>>,
+) -> FilterResult<(), LexingError> {
+    let remainder = lex.remainder().as_bytes();
+    let mut depth = 1;
+    let mut i = 0;
+    while i < remainder.len() {
+        if remainder[i..].starts_with(b"/..") {
+            depth += 1;
+            i += 3;
+        } else if remainder[i..].starts_with(b"../") {
+            depth -= 1;
+            i += 3;
+            if depth == 0 {
+                lex.bump(i);
+                return FilterResult::Skip;
+            }
+        } else {
+            if remainder[i] == b'\n' {
+                lex.extras.line += 1;
+            }
+            i += 1;
+        }
+    }
+    lex.bump(remainder.len());
+    FilterResult::Error(LexingError::UnterminatedBlockComment)
 }
 
 #[cfg(test)]
@@ -60,13 +338,13 @@ mod tests {
         assert_eq!(lex.next(), Some(Ok(Token::Ident)));
         assert_eq!(lex.slice(), "x");
         assert_eq!(lex.next(), Some(Ok(Token::Symbol("="))));
-        assert_eq!(lex.next(), Some(Ok(Token::Int(3))));
+        assert_eq!(lex.next(), Some(Ok(Token::Int(SmallValue::Untyped(3)))));
         assert_eq!(lex.next(), Some(Ok(Token::Symbol(","))));
 
         assert_eq!(lex.next(), Some(Ok(Token::Ident)));
         assert_eq!(lex.next(), Some(Ok(Token::Ident)));
         assert_eq!(lex.next(), Some(Ok(Token::Symbol("="))));
-        assert_eq!(lex.next(), Some(Ok(Token::Int(4))));
+        assert_eq!(lex.next(), Some(Ok(Token::Int(SmallValue::Untyped(4)))));
         assert_eq!(lex.next(), Some(Ok(Token::Symbol(","))));
 
         assert_eq!(lex.next(), Some(Ok(Token::Ident)));
@@ -117,7 +395,10 @@ mod tests {
         });
         assert_eq!(lex.next(), Some(Ok(Token::Ident)));
         assert_eq!(lex.slice(), "echo");
-        assert_eq!(lex.next(), Some(Ok(Token::String)));
+        assert_eq!(
+            lex.next(),
+            Some(Ok(Token::String(b"hello".as_slice().into())))
+        );
         assert_eq!(lex.slice(), "\"hello\"");
         assert_eq!(lex.next(), Some(Ok(Token::Symbol(","))));
 
@@ -130,17 +411,13 @@ mod tests {
         assert_eq!(lex.next(), Some(Ok(Token::Symbol(">"))));
         assert_eq!(lex.next(), Some(Ok(Token::Ident)));
         assert_eq!(lex.slice(), "y");
-        assert_eq!(lex.next(), Some(Ok(Token::Symbol("|"))));
-        assert_eq!(lex.next(), Some(Ok(Token::Symbol("|"))));
-        assert_eq!(lex.next(), Some(Ok(Token::Symbol("."))));
-        assert_eq!(lex.next(), Some(Ok(Token::Symbol("."))));
-        assert_eq!(lex.next(), Some(Ok(Token::Symbol("<"))));
-        assert_eq!(lex.next(), Some(Ok(Token::Ident)));
-        assert_eq!(lex.slice(), "y");
-        assert_eq!(lex.next(), Some(Ok(Token::Symbol("?"))));
+        assert_eq!(lex.next(), Some(Ok(Token::DocComment(".. < y ?"))));
 
         assert_eq!(lex.next(), Some(Ok(Token::Symbol(":"))));
-        assert_eq!(lex.next(), Some(Ok(Token::String)));
+        assert_eq!(
+            lex.next(),
+            Some(Ok(Token::String(b"hello".as_slice().into())))
+        );
         assert_eq!(lex.slice(), "\"hello\"");
         assert_eq!(lex.next(), Some(Ok(Token::Symbol(","))));
 
@@ -148,10 +425,185 @@ mod tests {
         assert_eq!(lex.slice(), "let");
         assert_eq!(lex.next(), Some(Ok(Token::Ident)));
         assert_eq!(lex.slice(), "x");
-        assert_eq!(lex.next(), Some(Ok(Token::Symbol("-"))));
-        assert_eq!(lex.next(), Some(Ok(Token::Symbol(">"))));
+        assert_eq!(lex.next(), Some(Ok(Token::RightArrow)));
         assert_eq!(lex.next(), Some(Ok(Token::Ident)));
         assert_eq!(lex.slice(), "y");
         assert_eq!(lex.next(), Some(Ok(Token::Symbol(","))));
     }
+
+    #[test]
+    fn comments() {
+        let source = r##"
+            // line comment
+            let x = /.. 1 ../ 2,
+            /..
+                /.. nested
+                ../
+                still a comment
+            ../
+            print x // trailing
+            "##;
+        let mut lex = Token::lexer_with_extras(source, FileContext {
+            filename: "test_comments".to_string(),
+            source,
+            line: 1,
+        });
+        assert_eq!(lex.next(), Some(Ok(Token::Ident)));
+        assert_eq!(lex.slice(), "let");
+        assert_eq!(lex.next(), Some(Ok(Token::Ident)));
+        assert_eq!(lex.next(), Some(Ok(Token::Symbol("="))));
+        assert_eq!(lex.next(), Some(Ok(Token::Int(SmallValue::Untyped(2)))));
+        assert_eq!(lex.next(), Some(Ok(Token::Symbol(","))));
+        assert_eq!(lex.extras.line, 3);
+
+        assert_eq!(lex.next(), Some(Ok(Token::Ident)));
+        assert_eq!(lex.slice(), "print");
+        assert_eq!(lex.extras.line, 9);
+        assert_eq!(lex.next(), Some(Ok(Token::Ident)));
+        assert_eq!(lex.slice(), "x");
+        assert_eq!(lex.next(), None);
+    }
+
+    #[test]
+    fn unterminated_block_comment() {
+        let source = r##"
+            x,
+            /.. /.. nested ../
+            never closed
+            "##;
+        let mut lex = Token::lexer_with_extras(source, FileContext {
+            filename: "test_unterminated".to_string(),
+            source,
+            line: 1,
+        });
+        assert_eq!(lex.next(), Some(Ok(Token::Ident)));
+        assert_eq!(lex.next(), Some(Ok(Token::Symbol(","))));
+        assert_eq!(lex.next(), Some(Err(LexingError::UnterminatedBlockComment)));
+        assert_eq!(lex.extras.location(lex.span().start), (3, 13));
+        assert_eq!(lex.next(), None);
+    }
+
+    #[test]
+    fn doc_comments() {
+        let source = r##"
+            | comment
+            ||
+            || Docs
+            ||   - indented
+            f = fn {},
+            "##;
+        let mut lex = Token::lexer_with_extras(source, FileContext {
+            filename: "test_doc_comments".to_string(),
+            source,
+            line: 1,
+        });
+        assert_eq!(lex.next(), Some(Ok(Token::DocComment(""))));
+        assert_eq!(lex.next(), Some(Ok(Token::DocComment("Docs"))));
+        assert_eq!(lex.next(), Some(Ok(Token::DocComment("  - indented"))));
+        assert_eq!(lex.next(), Some(Ok(Token::Ident)));
+        assert_eq!(lex.slice(), "f");
+    }
+
+    #[test]
+    fn spans() {
+        let source = "f = fn {},\n  f 42,\n";
+        let mut lex = Token::lexer_with_extras(source, FileContext {
+            filename: "test_spans".to_string(),
+            source,
+            line: 1,
+        });
+        while lex.next() != Some(Ok(Token::Int(SmallValue::Untyped(42)))) {}
+        assert_eq!(lex.span(), 15..17);
+        assert_eq!(lex.extras.location(lex.span().start), (2, 5));
+        assert_eq!(lex.extras.line_content(2), "  f 42,");
+    }
+
+    #[test]
+    fn int_literals() {
+        let source = "0x1F 0b1010 0o755 755o 1_000_000 255u8 4b 0x1Fu16 7u64 300u8 0b 3d 19o 0o";
+        let mut lex = Token::lexer_with_extras(source, FileContext {
+            filename: "test_int_literals".to_string(),
+            source,
+            line: 1,
+        });
+        assert_eq!(lex.next(), Some(Ok(Token::Int(SmallValue::Untyped(0x1F)))));
+        assert_eq!(
+            lex.next(),
+            Some(Ok(Token::Int(SmallValue::Untyped(0b1010))))
+        );
+        assert_eq!(lex.next(), Some(Ok(Token::Int(SmallValue::Untyped(0o755)))));
+        assert_eq!(lex.next(), Some(Ok(Token::Int(SmallValue::Untyped(0o755)))));
+        assert_eq!(
+            lex.next(),
+            Some(Ok(Token::Int(SmallValue::Untyped(1_000_000))))
+        );
+        assert_eq!(lex.next(), Some(Ok(Token::Int(SmallValue::Byte(255)))));
+        assert_eq!(lex.next(), Some(Ok(Token::Int(SmallValue::Byte(4)))));
+        assert_eq!(lex.next(), Some(Ok(Token::Int(SmallValue::Word(0x1F)))));
+        assert_eq!(lex.next(), Some(Ok(Token::Int(SmallValue::QWord(7)))));
+        assert_eq!(lex.next(), Some(Err(LexingError::IntegerTooLarge(255))));
+        assert_eq!(lex.next(), Some(Err(LexingError::MissingDigits(2))));
+        assert_eq!(lex.next(), Some(Ok(Token::Ident)));
+        assert_eq!(lex.slice(), "3d");
+        assert_eq!(lex.next(), Some(Err(LexingError::InvalidDigit(8))));
+        assert_eq!(lex.next(), Some(Err(LexingError::MissingDigits(8))));
+        assert_eq!(lex.next(), None);
+    }
+
+    #[test]
+    fn string_escapes() {
+        let source = r#""a\tb\n\"\\é" "x\qy" "\u12""#;
+        let mut lex = Token::lexer_with_extras(source, FileContext {
+            filename: "test_string_escapes".to_string(),
+            source,
+            line: 1,
+        });
+        assert_eq!(
+            lex.next(),
+            Some(Ok(Token::String("a\tb\n\"\\é".as_bytes().into())))
+        );
+        assert_eq!(lex.next(), Some(Err(LexingError::InvalidEscape(2..4))));
+        assert_eq!(lex.next(), Some(Err(LexingError::InvalidEscape(1..5))));
+        assert_eq!(lex.next(), None);
+    }
+
+    #[test]
+    fn format_strings() {
+        let source = r#"f"a {x} {{b}}\n" f"{x" f"\q{x}""#;
+        let mut lex = Token::lexer_with_extras(source, FileContext {
+            filename: "test_format_strings".to_string(),
+            source,
+            line: 1,
+        });
+        assert_eq!(
+            lex.next(),
+            Some(Ok(Token::FormatString(
+                vec![
+                    FormatChunk::Text(b"a ".as_slice().into(), 2..4),
+                    FormatChunk::Expr(5..6),
+                    FormatChunk::Text(b" {b}\n".as_slice().into(), 7..15),
+                ]
+                .into()
+            )))
+        );
+        assert_eq!(lex.next(), Some(Err(LexingError::InvalidFormatBrace(2..3))));
+        assert_eq!(lex.next(), Some(Err(LexingError::InvalidEscape(2..4))));
+        assert_eq!(lex.next(), None);
+    }
+
+    #[test]
+    fn registers() {
+        let source = "r0 r12 r255 r256 r1x";
+        let mut lex = Token::lexer_with_extras(source, FileContext {
+            filename: "test_registers".to_string(),
+            source,
+            line: 1,
+        });
+        assert_eq!(lex.next(), Some(Ok(Token::Reg(0))));
+        assert_eq!(lex.next(), Some(Ok(Token::Reg(12))));
+        assert_eq!(lex.next(), Some(Ok(Token::Reg(255))));
+        assert_eq!(lex.next(), Some(Err(LexingError::RegisterTooLarge)));
+        assert_eq!(lex.next(), Some(Ok(Token::Ident)));
+        assert_eq!(lex.next(), None);
+    }
 }
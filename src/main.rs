@@ -1,17 +1,17 @@
-#![feature(type_alias_impl_trait)]
-#![feature(trait_alias)]
-
 use std::{
-    collections::HashMap,
+    any::Any,
+    backtrace::{Backtrace, BacktraceStatus},
+    cell::RefCell,
     fs,
-    io::{self, Read, Write},
-    path::PathBuf,
+    io::{self, IsTerminal, Read, Write},
+    panic,
+    path::{Path, PathBuf},
 };
 
-use ast::{Ast, ProgramContext};
+use ast::{write_docs, Ast, ProgramContext};
 use clap::Parser as _;
-use cli::{Cli, CliSubCommand, ExpansionArgs, Verbosity};
-use errors::{CompileResult, ParsingError};
+use cli::{Cli, CliSubCommand, DocArgs, ExpansionArgs, MessageFormat, Verbosity};
+use errors::{CompileError, CompileResult, Diagnostic, ParsingError, Severity};
 use lexer::{FileContext, Token};
 use logos::Logos;
 use parser::Parser;
@@ -23,58 +23,136 @@ mod layers;
 mod lexer;
 mod parser;
 
+/// Exit status when the compiler crashed, `EX_SOFTWARE` of sysexits.h. Errors
+/// in the input exit with 1
+const ICE_EXIT_STATUS: i32 = 70;
+
+thread_local! {
+    /// Where the last panic happened, captured by the panic hook
+    static PANIC_BACKTRACE: RefCell<Option<Backtrace>> = const { RefCell::new(None) };
+}
+
 fn main() {
+    install_ice_hook();
     let cli = Cli::parse();
 
-    match &cli.command {
+    let result = panic::catch_unwind(|| match &cli.command {
         CliSubCommand::Expand(ref build_args) => build(&cli, build_args),
         CliSubCommand::Run(ref build_args) => {
             build(&cli, build_args);
-            todo!("And then run")
+            eprintln!("error: Running programs isn't supported yet, use `eas expand`");
+            std::process::exit(1);
         }
+        CliSubCommand::Doc(ref doc_args) => document(&cli, doc_args),
+    });
+    if let Err(payload) = result {
+        report_ice(payload.as_ref());
+        std::process::exit(ICE_EXIT_STATUS);
     }
 }
 
-fn build(cli: &Cli, build_args: &ExpansionArgs) {
-    let mut source = String::new();
-    let compilation_result = match build_args.input_files.as_deref() {
-        None => {
-            io::stdin()
-                .read_to_string(&mut source)
-                .expect("Could not read stdin (might be caused by not enough memory)");
+/// Replace Rust's panic message, panics are reported as internal compiler
+/// errors once they are caught
+fn install_ice_hook() {
+    panic::set_hook(Box::new(|_| {
+        PANIC_BACKTRACE.set(Some(Backtrace::capture()));
+    }));
+}
 
-            compile("stdin", &source, &cli, &build_args)
+/// Report a caught panic as an internal compiler error with a request for a
+/// bug report. Where in the compiler it happened is only shown with the
+/// backtrace, when `RUST_BACKTRACE` is set
+fn report_ice(payload: &(dyn Any + Send)) {
+    let message = match payload.downcast_ref::<&str>() {
+        Some(message) => message.to_string(),
+        None => match payload.downcast_ref::<String>() {
+            Some(message) => message.clone(),
+            None => "unknown panic".to_string(),
+        },
+    };
+    eprintln!("error: internal compiler error: {message}");
+    eprintln!("note: the compiler unexpectedly crashed, this is a bug");
+    eprintln!(
+        "note: please file a bug report with the input that caused it (eas {})",
+        env!("CARGO_PKG_VERSION")
+    );
+    if let Some(backtrace) = PANIC_BACKTRACE.take() {
+        if backtrace.status() == BacktraceStatus::Captured {
+            eprintln!("\n{backtrace}");
         }
-        Some([ref file]) => {
-            fs::File::open(file)
-                .unwrap()
-                .read_to_string(&mut source)
-                .expect("Could not read input file (might be caused by not enough memory)");
-
-            compile(
-                file.file_stem().unwrap().to_str().unwrap(),
-                &source,
-                &cli,
-                &build_args,
-            )
+    }
+}
+
+/// Read a whole input file, or stdin if there is no file
+fn read_input(file: Option<&Path>) -> String {
+    let mut source = String::new();
+    let result = match file {
+        None => io::stdin().read_to_string(&mut source),
+        Some(file) => fs::File::open(file).and_then(|mut file| file.read_to_string(&mut source)),
+    };
+    if let Err(error) = result {
+        let name = file.map_or("stdin".to_string(), |file| file.display().to_string());
+        eprintln!("error: Could not read {name}: {error}");
+        std::process::exit(1);
+    }
+    source
+}
+
+/// Name of the outputs of a file, e.g. `main` for `src/main.exas`
+fn file_stem(file: &Path) -> &str {
+    file.file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("out")
+}
+
+fn build(cli: &Cli, build_args: &ExpansionArgs) {
+    let file = match build_args.input_files.as_deref() {
+        None => None,
+        Some([ref file]) => Some(file.as_path()),
+        Some([..]) => {
+            eprintln!("error: Only a single input file is supported");
+            std::process::exit(1);
         }
-        Some([..]) => panic!("Too many files"),
+    };
+    let source = read_input(file);
+    let compilation_result = match file {
+        None => compile("stdin", "stdin".to_string(), &source, cli, build_args),
+        Some(file) => compile(
+            file_stem(file),
+            file.display().to_string(),
+            &source,
+            cli,
+            build_args,
+        ),
     };
 
     if let Err(error) = compilation_result {
-        eprintln!("Compilation failed: {}", error);
+        match build_args.message_format {
+            MessageFormat::Human => {
+                eprint!("{}", error.render(use_color()));
+                eprintln!("Compilation failed");
+            }
+            MessageFormat::Json => eprintln!("{}", error.to_json()),
+        }
         std::process::exit(1);
     }
 }
 
+/// Whether diagnostics on stderr should be colored
+fn use_color() -> bool {
+    io::stderr().is_terminal() && std::env::var_os("NO_COLOR").is_none()
+}
+
+/// `name` is used for emitted files, `filename` for diagnostics
 fn compile<'source>(
     name: &'source str,
+    filename: String,
     source: &'source str,
     cli: &Cli,
     build_args: &ExpansionArgs,
 ) -> CompileResult<'source, ()> {
     let file_context = FileContext {
-        filename: name.to_string(),
+        filename,
         line: 1,
         source,
     };
@@ -97,15 +175,19 @@ fn compile<'source>(
     }
 
     // ==========  Program Context  =========== //
-    let mut program_ctx = ProgramContext {
-        symbols: HashMap::new(),
-        types: HashMap::new(),
-        file_context: FileContext {
-            line: 1,
-            ..file_context
-        },
-    };
+    let mut program_ctx = ProgramContext::new(file_context);
     ast.build_context(&mut program_ctx);
+    let (errors, warnings): (Vec<_>, Vec<_>) = program_ctx
+        .diagnostics
+        .drain(..)
+        .partition(|diagnostic| diagnostic.severity == Severity::Error);
+    report_warnings(&warnings, &program_ctx.file_context, build_args);
+    if !errors.is_empty() {
+        return Err(CompileError::DefinitionErrors(
+            errors,
+            program_ctx.file_context,
+        ));
+    }
     if let Some(path) = build_args.symbols.clone().or_else(|| {
         if build_args.all {
             Some(PathBuf::from(format!("{}.sym", name)))
@@ -138,8 +220,7 @@ fn compile<'source>(
         }
     };
     if let Err(e) = result {
-        println!("\n{}", e);
-        return Ok(());
+        return Err(CompileError::CheckError(e, program_ctx.file_context));
     } else {
         println!("\nNo errors :)");
     }
@@ -157,9 +238,74 @@ fn compile<'source>(
     Ok(())
 }
 
+fn report_warnings(warnings: &[Diagnostic], context: &FileContext, build_args: &ExpansionArgs) {
+    for warning in warnings {
+        match build_args.message_format {
+            MessageFormat::Human => eprintln!("{}", warning.render(context, use_color())),
+            MessageFormat::Json => eprintln!("{}", warning.to_json(context)),
+        }
+    }
+}
+
+fn document(cli: &Cli, doc_args: &DocArgs) {
+    let sources = match doc_args.input_files.as_deref() {
+        None => vec![("stdin".to_string(), read_input(None))],
+        Some(files) => files
+            .iter()
+            .map(|file| (file_stem(file).to_string(), read_input(Some(file))))
+            .collect(),
+    };
+
+    for (name, source) in &sources {
+        if let Err(error) = document_file(name, source, cli, doc_args) {
+            eprint!("{}", error.render(use_color()));
+            eprintln!("Documentation failed");
+            std::process::exit(1);
+        }
+    }
+}
+
+fn document_file<'source>(
+    name: &'source str,
+    source: &'source str,
+    cli: &Cli,
+    doc_args: &DocArgs,
+) -> CompileResult<'source, ()> {
+    let file_context = FileContext {
+        filename: name.to_string(),
+        line: 1,
+        source,
+    };
+    let ast = get_ast(source, file_context.clone())?;
+    let mut program_ctx = ProgramContext::new(file_context);
+    ast.build_context(&mut program_ctx);
+
+    match doc_args.output {
+        Some(ref dir) => {
+            fs::create_dir_all(dir)?;
+            let path = dir.join(format!("{}.{}", name, doc_args.format.extension()));
+            if cli.verbosity >= Verbosity::Info {
+                println!("Emitting docs to {}", path.display());
+            }
+            let mut doc_file = fs::File::create(&path)?;
+            write_docs(&mut doc_file, name, &program_ctx, doc_args.format)?;
+        }
+        None => write_docs(
+            &mut io::stdout().lock(),
+            name,
+            &program_ctx,
+            doc_args.format,
+        )?,
+    }
+    Ok(())
+}
+
 fn get_ast<'source>(
     input: &'source str,
     file_context: FileContext<'source>,
-) -> Result<Ast<'source>, ParsingError<'source>> {
-    Parser::new(Token::lexer_with_extras(input, file_context)).parse()
+) -> Result<Ast<'source>, Vec<ParsingError<'source>>> {
+    match Parser::new(Token::lexer_with_extras(input, file_context)).parse() {
+        (ast, errors) if errors.is_empty() => Ok(ast),
+        (_, errors) => Err(errors),
+    }
 }
@@ -1,6 +1,10 @@
 use logos::Logos;
 
-use crate::{ast::Ast, errors::ParsingError, lexer::Token};
+use crate::{
+    ast::Ast,
+    errors::ParsingError,
+    lexer::{Span, Token},
+};
 
 pub type LogosError<'source> = <Token<'source> as Logos<'source>>::Error;
 pub type LogosLexer<'source> = logos::Lexer<'source, Token<'source>>;
@@ -15,6 +19,15 @@ pub struct Parser<'source> {
     pub lexer: LogosLexer<'source>,
     pub current_token: Option<Result<Token<'source>, LogosError<'source>>>,
     pub current_slice: &'source str,
+    pub current_span: Span,
+    /// End of the last token that was advanced past
+    pub last_end: usize,
+    /// Doc comments read since the last call to `take_docs`
+    pub docs: Vec<&'source str>,
+    /// Errors that were recovered from, see `recover`
+    pub errors: Vec<ParsingError<'source>>,
+    /// Number of lists that are currently being parsed, 1 at the top level
+    pub list_depth: usize,
 }
 
 impl<'source> Parser<'source> {
@@ -23,16 +36,172 @@ impl<'source> Parser<'source> {
             lexer,
             current_token: None,
             current_slice: "",
+            current_span: 0..0,
+            last_end: 0,
+            docs: vec![],
+            errors: vec![],
+            list_depth: 0,
         }
     }
 
-    pub fn parse(&mut self) -> Result<Ast<'source>, ParsingError<'source>> {
+    /// Parse the whole file. The returned `Ast` only contains the parts that
+    /// could be parsed if there are errors
+    pub fn parse(&mut self) -> (Ast<'source>, Vec<ParsingError<'source>>) {
         self.advance();
-        Ast::parse(self)
+        let ast = Ast::parse(self).unwrap_or_else(|error| {
+            self.errors.push(error);
+            Ast::default()
+        });
+        (ast, std::mem::take(&mut self.errors))
     }
 
+    /// Move to the next token. Doc comments are collected on the way and can be
+    /// retrieved with `take_docs`
     pub fn advance(&mut self) {
-        self.current_token = self.lexer.next();
-        self.current_slice = self.lexer.slice();
+        self.last_end = self.current_span.end;
+        loop {
+            self.current_token = self.lexer.next();
+            self.current_slice = self.lexer.slice();
+            self.current_span = self.lexer.span();
+            match self.current_token {
+                Some(Ok(Token::DocComment(text))) => self.docs.push(text),
+                _ => break,
+            }
+        }
+    }
+
+    /// Span from `start` up to the end of the last token that was advanced past
+    pub fn span_from(&self, start: usize) -> Span {
+        start..self.last_end.max(start)
+    }
+
+    /// Record `error` and skip ahead to the next `,` or to the `}` / `]` that
+    /// closes the current list, so parsing can continue after a syntax error.
+    /// Brackets opened while skipping are skipped as a whole, and so are
+    /// unmatched closing brackets at the top level
+    pub fn recover(&mut self, error: ParsingError<'source>) {
+        self.errors.push(error);
+        self.take_docs();
+        let mut depth = 0usize;
+        loop {
+            match self.current_token {
+                None => break,
+                Some(Ok(Token::Symbol("{" | "[" | "("))) => depth += 1,
+                Some(Ok(Token::Symbol("}" | "]"))) if depth == 0 && self.list_depth > 1 => break,
+                Some(Ok(Token::Symbol("}" | "]" | ")"))) => depth = depth.saturating_sub(1),
+                Some(Ok(Token::Symbol(","))) if depth == 0 => {
+                    self.advance();
+                    break;
+                }
+                _ => {}
+            }
+            self.advance();
+        }
+    }
+
+    /// Take the doc comments that precede the current token
+    pub fn take_docs(&mut self) -> Vec<&'source str> {
+        std::mem::take(&mut self.docs)
+    }
+}
+
+/// Compile `source` and return the emitted code, or the diagnostics of the
+/// first stage that fails. Warnings are only returned along with errors
+#[cfg(test)]
+pub fn compile_to_string(source: &str) -> Result<String, Vec<crate::errors::Diagnostic>> {
+    use crate::{
+        ast::ProgramContext,
+        errors::{CheckError, Severity},
+        lexer::FileContext,
+    };
+
+    let context = FileContext {
+        filename: "test".to_string(),
+        source,
+        line: 1,
+    };
+    let (ast, errors) = Parser::new(Token::lexer_with_extras(source, context.clone())).parse();
+    if !errors.is_empty() {
+        return Err(errors.iter().map(ParsingError::diagnostic).collect());
+    }
+    let mut ctx = ProgramContext::new(context);
+    ast.build_context(&mut ctx);
+    if ctx
+        .diagnostics
+        .iter()
+        .any(|diagnostic| diagnostic.severity == Severity::Error)
+    {
+        return Err(ctx.diagnostics);
+    }
+    let mut output = vec![];
+    match ast.expand_clayer(&mut output, &ctx) {
+        Ok(()) => Ok(String::from_utf8(output).expect("the output is UTF-8")),
+        Err(CheckError::CompileError(diagnostic)) => Err(vec![*diagnostic]),
+        Err(CheckError::EmitError(error)) => panic!("writing to memory failed: {error}"),
+    }
+}
+
+/// The lines of `output` after the line `marker`, up to the next empty line,
+/// without indentation
+#[cfg(test)]
+pub fn emitted_after<'a>(output: &'a str, marker: &str) -> Vec<&'a str> {
+    let mut lines = output.lines().map(str::trim);
+    if !lines.any(|line| line == marker) {
+        panic!("no line `{marker}` in the output:\n{output}");
+    }
+    lines.take_while(|line| !line.is_empty()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::FileContext;
+
+    #[test]
+    fn recover_from_errors() {
+        let source = "f = fn 5 { ) },\n= 3,\nx = (,\ny = 4,\n";
+        let context = FileContext {
+            filename: "test_recover".to_string(),
+            source,
+            line: 1,
+        };
+        let (ast, errors) = Parser::new(Token::lexer_with_extras(source, context)).parse();
+        let error_starts = errors
+            .iter()
+            .map(|error| error.span().start)
+            .collect::<Vec<_>>();
+        assert_eq!(error_starts, vec![11, 16, 26]);
+        // `f` and `y` are still parsed
+        assert_eq!(ast.program.elements.len(), 2);
+    }
+
+    #[test]
+    fn report_unsupported_input_instead_of_panicking() {
+        let cases = [
+            (
+                "T = type 2, f = fn [a: T] {}, f (fn {}),",
+                "signature-mismatch",
+            ),
+            ("f = fn [a] {}, f 3,", "unknown-size"),
+            ("x = y = 2,", "chained-assign"),
+        ];
+        for (source, code) in cases {
+            let errors = compile_to_string(source).unwrap_err();
+            assert_eq!(errors[0].code, Some(code), "{source}");
+        }
+        assert!(compile_to_string("x = \"hi\",").is_ok());
+    }
+
+    #[test]
+    fn unexpected_token_message() {
+        let cases = [
+            ("x = <- 3,", "Unexpected `<-` while parsing expr"),
+            ("x = -> 3,", "Unexpected `->` while parsing expr"),
+            ("x = ), ", "Unexpected `)` while parsing expr"),
+        ];
+        for (source, message) in cases {
+            let errors = compile_to_string(source).unwrap_err();
+            assert_eq!(errors[0].message, message, "{source}");
+        }
     }
 }